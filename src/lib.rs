@@ -3,4 +3,10 @@ pub mod transport;
 pub mod server;
 
 pub use server::McpServer;
-pub use transport::stdio::StdioTransport;
\ No newline at end of file
+pub use transport::lines::LineTransport;
+pub use transport::stdio::StdioTransport;
+pub use transport::http::HttpListener;
+pub use transport::sse::SseListener;
+pub use transport::websocket::WebSocketListener;
+#[cfg(unix)]
+pub use transport::unix::UnixSocketListener;
\ No newline at end of file
@@ -1,26 +1,152 @@
 use anyhow::Result;
-use tracing_subscriber;
-use ankiconnect_mcp::{McpServer, StdioTransport};
-use ankiconnect_mcp::server::anki_tools::ListDecksTool;
+use clap::{Parser, ValueEnum};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use ankiconnect_mcp::{HttpListener, McpServer, SseListener, StdioTransport, WebSocketListener};
+#[cfg(unix)]
+use ankiconnect_mcp::UnixSocketListener;
+use ankiconnect_mcp::server::anki_tools::{
+    AddNoteTool, AddNotesTool, CreateDeckTool, CreateNoteTypeTool, DEFAULT_MAX_CARDS_PER_CHANGE,
+    DeleteDeckTool, DeleteNotesTool, DeleteUnusedMediaTool, EditNoteTypeFieldsTool, FindNotesTool,
+    GetCardsTool, GetNoteTypeTool, ListDecksTool, ListMediaFilesTool, ListNoteTypesTool, MediaPathAccess,
+    RenameDeckTool, RetrieveMediaFileTool, ReviewStatsTool, ScheduleAction, ScheduleCardsTool,
+    SetDueDateTool, StoreMediaFileTool, UpdateNoteFieldsTool, UpdateNoteTagsTool,
+    UpdateNoteTypeStylingTool, UpdateNoteTypeTemplatesTool,
+};
+use ankiconnect_mcp::server::anki_connect;
+use ankiconnect_mcp::server::anki_prompts::{MakeClozeCardsPrompt, ReviewLeechesPrompt, StudyProgressPrompt};
+use ankiconnect_mcp::server::anki_resources::{DeckResource, NoteResource};
 use ankiconnect_mcp::server::resources::StaticTextResource;
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum TransportKind {
+    /// JSON-RPC over stdin/stdout for a single client
+    Stdio,
+    /// Streamable HTTP on a single endpoint (/mcp) for any number of clients
+    Http,
+    /// Legacy HTTP+SSE (GET /sse, POST /messages) for clients of MCP 2024-11-05
+    Sse,
+    /// WebSocket on /ws, one text message per JSON-RPC message
+    Websocket,
+    /// Unix domain socket, so local clients can share one server process
+    Unix,
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about = "MCP server for Anki via AnkiConnect")]
+struct Args {
+    /// Transport to serve MCP over
+    #[arg(long, value_enum, default_value = "stdio")]
+    transport: TransportKind,
+
+    /// Address to listen on for network transports
+    #[arg(long, default_value = "127.0.0.1:3000")]
+    bind: SocketAddr,
+
+    /// Socket file for the unix transport [default: $XDG_RUNTIME_DIR/ankiconnect-mcp.sock,
+    /// or ankiconnect-mcp.sock in the temporary directory]
+    #[arg(long)]
+    socket: Option<PathBuf>,
+
+    /// Host AnkiConnect listens on
+    #[arg(long, default_value = anki_connect::DEFAULT_HOST)]
+    anki_host: String,
+
+    /// Port AnkiConnect listens on
+    #[arg(long, default_value_t = anki_connect::DEFAULT_PORT)]
+    anki_port: u16,
+
+    /// Directory store_media_file may read files from [default: any file over stdio,
+    /// none over the other transports]
+    #[arg(long)]
+    media_dir: Option<PathBuf>,
+}
+
+#[cfg(unix)]
+fn default_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ankiconnect-mcp.sock")
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = Args::parse();
+
     tracing_subscriber::fmt()
         .with_writer(std::io::stderr)
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
+    anki_connect::configure(&args.anki_host, args.anki_port)?;
+
+    let max_cards_per_change = std::env::var("ANKICONNECT_MCP_MAX_CARDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_CARDS_PER_CHANGE);
+
+    // Other transports serve any client that can connect, which must not get
+    // to read the server's files through the media folder
+    let media_paths = match (&args.media_dir, args.transport) {
+        (Some(dir), _) => MediaPathAccess::Within(std::fs::canonicalize(dir)
+            .map_err(|e| anyhow::anyhow!("Invalid --media-dir {}: {}", dir.display(), e))?),
+        (None, TransportKind::Stdio) => MediaPathAccess::Any,
+        (None, _) => MediaPathAccess::Disabled,
+    };
+
     let mut server = McpServer::new();
 
+    if let Some(secs) = std::env::var("ANKICONNECT_MCP_POLL_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+    {
+        server.set_subscription_poll_interval(std::time::Duration::from_secs(secs));
+    }
+
     server.add_tool("list_decks".to_string(), Box::new(ListDecksTool));
+    server.add_tool("create_deck".to_string(), Box::new(CreateDeckTool));
+    server.add_tool("rename_deck".to_string(), Box::new(RenameDeckTool));
+    server.add_tool("delete_deck".to_string(), Box::new(DeleteDeckTool));
+    server.add_tool("list_note_types".to_string(), Box::new(ListNoteTypesTool));
+    server.add_tool("get_note_type".to_string(), Box::new(GetNoteTypeTool));
+    server.add_tool("create_note_type".to_string(), Box::new(CreateNoteTypeTool));
+    server.add_tool("edit_note_type_fields".to_string(), Box::new(EditNoteTypeFieldsTool));
+    server.add_tool("update_note_type_templates".to_string(), Box::new(UpdateNoteTypeTemplatesTool));
+    server.add_tool("update_note_type_styling".to_string(), Box::new(UpdateNoteTypeStylingTool));
+    server.add_tool("find_notes".to_string(), Box::new(FindNotesTool));
+    server.add_tool("add_note".to_string(), Box::new(AddNoteTool));
+    server.add_tool("add_notes".to_string(), Box::new(AddNotesTool));
+    server.add_tool("get_cards".to_string(), Box::new(GetCardsTool));
+    server.add_tool("review_stats".to_string(), Box::new(ReviewStatsTool));
+    for (name, action) in [
+        ("suspend_cards", ScheduleAction::Suspend),
+        ("unsuspend_cards", ScheduleAction::Unsuspend),
+        ("bury_cards", ScheduleAction::Bury),
+        ("forget_cards", ScheduleAction::Forget),
+    ] {
+        server.add_tool(name.to_string(), Box::new(ScheduleCardsTool::new(action, max_cards_per_change)));
+    }
+    server.add_tool("set_due_date".to_string(), Box::new(SetDueDateTool::new(max_cards_per_change)));
+    server.add_tool("store_media_file".to_string(), Box::new(StoreMediaFileTool::new(media_paths)));
+    server.add_tool("retrieve_media_file".to_string(), Box::new(RetrieveMediaFileTool));
+    server.add_tool("list_media_files".to_string(), Box::new(ListMediaFilesTool));
+    server.add_tool("delete_unused_media".to_string(), Box::new(DeleteUnusedMediaTool::new()));
+    server.add_tool("update_note_fields".to_string(), Box::new(UpdateNoteFieldsTool));
+    server.add_tool("update_note_tags".to_string(), Box::new(UpdateNoteTagsTool));
+    server.add_tool("delete_notes".to_string(), Box::new(DeleteNotesTool::new()));
+
+    server.add_prompt("make_cloze_cards".to_string(), Box::new(MakeClozeCardsPrompt));
+    server.add_prompt("review_leeches".to_string(), Box::new(ReviewLeechesPrompt));
+    server.add_prompt("summarize_study_progress".to_string(), Box::new(StudyProgressPrompt));
 
     server.add_resource(
         "anki://connection-help".to_string(),
         Box::new(StaticTextResource::new(
             "connection-help".to_string(),
             "Help for connecting to AnkiConnect".to_string(),
-            r#"AnkiConnect Setup Instructions:
+            format!(r#"AnkiConnect Setup Instructions:
 
 1. Install Anki desktop application
 2. Install AnkiConnect plugin:
@@ -29,15 +155,15 @@ async fn main() -> Result<()> {
    - Enter code: 2055492159
    - Click OK and restart Anki
 3. Verify AnkiConnect is running:
-   - Visit http://localhost:8765 in your browser
+   - Visit http://{address} in your browser
    - You should see "AnkiConnect v.X" displayed
 4. Keep Anki open while using this MCP server
 
 Troubleshooting:
 - If connection fails, ensure Anki is running
 - Check that AnkiConnect plugin is enabled
-- Verify no firewall is blocking port 8765
-- Try restarting Anki if the plugin was just installed"#.to_string(),
+- Verify no firewall is blocking port {port}
+- Try restarting Anki if the plugin was just installed"#, address = anki_connect::address(), port = args.anki_port),
         )),
     );
 
@@ -46,12 +172,49 @@ Troubleshooting:
         Box::new(StaticTextResource::new(
             "about".to_string(),
             "About the AnkiConnect MCP Server".to_string(),
-            r#"AnkiConnect MCP Server
+            format!(r#"AnkiConnect MCP Server
 
 This Model Context Protocol (MCP) server provides integration with Anki flashcard software through the AnkiConnect plugin.
 
 Available Tools:
 - list_decks: Retrieves all Anki decks with statistics, hierarchy, and card information
+- create_deck: Creates a deck (and missing parent decks)
+- rename_deck: Renames or re-parents a deck together with its subdecks and cards
+- delete_deck: Deletes a deck, either deleting its cards or moving them to another deck
+- list_note_types: Lists note types with their fields, templates and cloze flag
+- get_note_type: Returns a note type's fields, card templates and CSS
+- create_note_type: Creates a note type from fields, card templates and CSS
+- edit_note_type_fields: Adds, renames, reorders or removes fields of a note type
+- update_note_type_templates: Updates or adds card templates of a note type
+- update_note_type_styling: Replaces the CSS of a note type
+- find_notes: Searches notes with Anki search syntax and returns their fields, tags and cards
+- add_note: Creates a new note in a deck from a note type and field values
+- add_notes: Creates many notes at once with a per-note result for each
+- get_cards: Returns scheduling data (interval, ease, due, lapses, state) for cards
+- review_stats: Computes retention, review load, answer time, lapse rate and a due forecast
+- suspend_cards / unsuspend_cards: Suspends or unsuspends cards by ID or search query
+- bury_cards: Buries cards until the next day
+- forget_cards: Resets cards to new
+- set_due_date: Sets the due date (and optionally the interval) of cards
+- store_media_file: Stores a media file from base64 data or a local path
+- retrieve_media_file: Fetches a media file as image or embedded resource content
+- list_media_files: Lists media files matching a glob pattern
+- delete_unused_media: Finds and deletes media files no note refers to
+- update_note_fields: Changes some fields of a note and reports the before/after values
+- update_note_tags: Adds, removes or replaces a note's tags and reports the before/after tags
+- delete_notes: Deletes notes after a mandatory dry-run preview and confirmation token
+
+Prompts:
+- make_cloze_cards: Turns a piece of text into cloze notes for one of your decks
+- review_leeches: Diagnoses your leech cards and proposes fixes
+- summarize_study_progress: Summarizes recent reviews, retention and upcoming workload
+
+Resource Templates:
+- anki://deck/{{name}}: A deck's statistics, card and note counts and subdecks
+- anki://note/{{id}}: A note's fields, tags and cards
+
+Deck and note resources can be subscribed to; the server polls AnkiConnect and
+sends notifications/resources/updated when their contents change.
 
 Features:
 - Comprehensive deck information including statistics
@@ -63,14 +226,33 @@ Features:
 Requirements:
 - Anki desktop application
 - AnkiConnect plugin (code: 2055492159)
-- Anki running with AnkiConnect accessible on localhost:8765
+- Anki running with AnkiConnect accessible on {address}
 
-This server implements MCP specification 2025-03-26 and provides a foundation for building AI assistants that can interact with your Anki flashcard collection."#.to_string(),
+This server supports MCP specification revisions 2024-11-05, 2025-03-26 and 2025-06-18 and provides a foundation for building AI assistants that can interact with your Anki flashcard collection."#, address = anki_connect::address()),
         )),
     );
 
-    let transport = StdioTransport::new();
-    server.run(transport).await?;
+    server.add_resource_template("anki://deck/{name}".to_string(), Box::new(DeckResource));
+    server.add_resource_template("anki://note/{id}".to_string(), Box::new(NoteResource));
+
+    match args.transport {
+        TransportKind::Stdio => server.run(StdioTransport::new()).await?,
+        TransportKind::Http => server.serve(HttpListener::bind(args.bind).await?).await?,
+        TransportKind::Sse => server.serve(SseListener::bind(args.bind).await?).await?,
+        TransportKind::Websocket => server.serve(WebSocketListener::bind(args.bind).await?).await?,
+        #[cfg(unix)]
+        TransportKind::Unix => {
+            let path = args.socket.unwrap_or_else(default_socket_path);
+            let listener = UnixSocketListener::bind(&path).await?;
+            // Returning on Ctrl-C drops the listener, which removes the socket file
+            tokio::select! {
+                result = server.serve(listener) => result?,
+                _ = tokio::signal::ctrl_c() => tracing::info!("Shutting down"),
+            }
+        }
+        #[cfg(not(unix))]
+        TransportKind::Unix => anyhow::bail!("Unix domain sockets are not supported on this platform"),
+    }
 
     Ok(())
 }
\ No newline at end of file
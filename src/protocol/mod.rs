@@ -1,7 +1,9 @@
 pub mod messages;
 pub mod errors;
+pub mod version;
 
 pub use messages::*;
 pub use errors::*;
+pub use version::*;
 
-pub const MCP_VERSION: &str = "2024-11-05";
\ No newline at end of file
+pub const MCP_VERSION: &str = ProtocolVersion::LATEST.as_str();
\ No newline at end of file
@@ -0,0 +1,52 @@
+/// The MCP specification revisions this server speaks, oldest first so that
+/// comparisons tell whether a feature is available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    V2024_11_05,
+    V2025_03_26,
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    pub const LATEST: Self = Self::V2025_06_18;
+    pub const ALL: [Self; 3] = [Self::V2024_11_05, Self::V2025_03_26, Self::V2025_06_18];
+
+    pub fn parse(version: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.as_str() == version)
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::V2024_11_05 => "2024-11-05",
+            Self::V2025_03_26 => "2025-03-26",
+            Self::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    /// The version to answer an `initialize` with: the client's if we support
+    /// it, otherwise the latest one, leaving the client to disconnect if it
+    /// cannot speak that.
+    pub fn negotiate(requested: &str) -> Self {
+        Self::parse(requested).unwrap_or(Self::LATEST)
+    }
+
+    /// Tool annotations such as `readOnlyHint`, and the `message` of progress notifications.
+    pub fn supports_tool_annotations(self) -> bool {
+        self >= Self::V2025_03_26
+    }
+
+    pub fn supports_progress_messages(self) -> bool {
+        self >= Self::V2025_03_26
+    }
+
+    /// `outputSchema` on tools and `structuredContent` on their results.
+    pub fn supports_structured_content(self) -> bool {
+        self >= Self::V2025_06_18
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
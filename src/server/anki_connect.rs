@@ -0,0 +1,465 @@
+// ankiconnect-rs does not expose every AnkiConnect action (findNotes, notesInfo, ...),
+// so the tools fall back to calling the HTTP API directly for those.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::OnceLock;
+use ankiconnect_rs::AnkiClient;
+
+pub const DEFAULT_HOST: &str = "localhost";
+pub const DEFAULT_PORT: u16 = 8765;
+const ANKICONNECT_API_VERSION: u8 = 6;
+
+/// The AnkiConnect endpoint shared by every tool, prompt, resource and session.
+struct Connection {
+    address: String,
+    url: String,
+    client: AnkiClient,
+}
+
+impl Connection {
+    fn new(host: &str, port: u16) -> Self {
+        let address = format!("{}:{}", host, port);
+        Self {
+            url: format!("http://{}", address),
+            address,
+            client: AnkiClient::with_connection(host, port),
+        }
+    }
+}
+
+static CONNECTION: OnceLock<Connection> = OnceLock::new();
+
+/// Points all AnkiConnect calls at `host:port`. Must be called before the
+/// first call; fails if the connection is already in use.
+pub fn configure(host: &str, port: u16) -> Result<()> {
+    CONNECTION
+        .set(Connection::new(host, port))
+        .map_err(|_| anyhow!("AnkiConnect connection is already configured"))
+}
+
+fn connection() -> &'static Connection {
+    CONNECTION.get_or_init(|| Connection::new(DEFAULT_HOST, DEFAULT_PORT))
+}
+
+/// The shared ankiconnect-rs client.
+pub fn client() -> &'static AnkiClient {
+    &connection().client
+}
+
+/// `host:port` of AnkiConnect, for error messages.
+pub fn address() -> &'static str {
+    &connection().address
+}
+
+#[derive(Debug, Deserialize)]
+struct AnkiConnectResponse {
+    result: serde_json::Value,
+    error: Option<String>,
+}
+
+/// Sends a raw AnkiConnect action and deserializes its `result`.
+pub fn invoke<R: DeserializeOwned>(action: &str, params: serde_json::Value) -> Result<R> {
+    let request = serde_json::json!({
+        "action": action,
+        "version": ANKICONNECT_API_VERSION,
+        "params": params,
+    });
+
+    let mut response = ureq::post(&connection().url)
+        .send_json(&request)
+        .map_err(|e| anyhow!(
+            "HTTP request to AnkiConnect at {} failed: {}. Please ensure Anki is running and the AnkiConnect plugin is installed",
+            address(),
+            e
+        ))?;
+
+    let response: AnkiConnectResponse = response
+        .body_mut()
+        .read_json()
+        .map_err(|e| anyhow!("Failed to parse AnkiConnect response: {}", e))?;
+
+    if let Some(error) = response.error {
+        return Err(anyhow!("AnkiConnect action '{}' failed: {}", action, error));
+    }
+
+    serde_json::from_value(response.result)
+        .map_err(|e| anyhow!("Unexpected result for AnkiConnect action '{}': {}", action, e))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NoteFieldDto {
+    pub value: String,
+    pub order: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteInfoDto {
+    pub note_id: u64,
+    pub model_name: String,
+    pub tags: Vec<String>,
+    pub fields: HashMap<String, NoteFieldDto>,
+    #[serde(default)]
+    pub cards: Vec<u64>,
+}
+
+impl NoteInfoDto {
+    /// Returns the note's fields as (name, value) pairs in note type order.
+    pub fn ordered_fields(&self) -> Vec<(&str, &str)> {
+        let mut fields: Vec<(&str, &NoteFieldDto)> = self.fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field))
+            .collect();
+        fields.sort_by_key(|(_, field)| field.order);
+        fields.into_iter().map(|(name, field)| (name, field.value.as_str())).collect()
+    }
+}
+
+pub fn find_notes(query: &str) -> Result<Vec<u64>> {
+    invoke("findNotes", serde_json::json!({ "query": query }))
+}
+
+/// Fetches note details. IDs that no longer exist are skipped.
+pub fn notes_info(note_ids: &[u64]) -> Result<Vec<NoteInfoDto>> {
+    if note_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // AnkiConnect answers unknown note IDs with an empty object
+    let raw: Vec<serde_json::Value> = invoke("notesInfo", serde_json::json!({ "notes": note_ids }))?;
+
+    raw.into_iter()
+        .filter(|value| value.as_object().is_some_and(|note| !note.is_empty()))
+        .map(|value| serde_json::from_value(value)
+            .map_err(|e| anyhow!("Unexpected note in result of AnkiConnect action 'notesInfo': {}", e)))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanAddNoteDto {
+    pub can_add: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Checks notes without adding them, with the reason for each one that can't be added.
+pub fn can_add_notes_with_error_detail(notes: &[serde_json::Value]) -> Result<Vec<CanAddNoteDto>> {
+    if notes.is_empty() {
+        return Ok(Vec::new());
+    }
+    invoke("canAddNotesWithErrorDetail", serde_json::json!({ "notes": notes }))
+}
+
+/// Adds notes in one call, returning `None` for each note that was not added.
+pub fn add_notes(notes: &[serde_json::Value]) -> Result<Vec<Option<u64>>> {
+    if notes.is_empty() {
+        return Ok(Vec::new());
+    }
+    invoke("addNotes", serde_json::json!({ "notes": notes }))
+}
+
+pub fn update_note_fields(note_id: u64, fields: &HashMap<String, String>) -> Result<()> {
+    invoke("updateNoteFields", serde_json::json!({
+        "note": { "id": note_id, "fields": fields }
+    }))
+}
+
+pub fn add_tags(note_ids: &[u64], tags: &[String]) -> Result<()> {
+    invoke("addTags", serde_json::json!({ "notes": note_ids, "tags": tags.join(" ") }))
+}
+
+pub fn remove_tags(note_ids: &[u64], tags: &[String]) -> Result<()> {
+    invoke("removeTags", serde_json::json!({ "notes": note_ids, "tags": tags.join(" ") }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardInfoDto {
+    pub card_id: u64,
+    pub note: u64,
+    pub deck_name: String,
+    #[serde(default)]
+    pub model_name: String,
+    #[serde(default)]
+    pub ord: usize,
+    /// 0 = new, 1 = learning, 2 = review, 3 = relearning
+    #[serde(rename = "type", default)]
+    pub card_type: i32,
+    /// -3/-2 = buried, -1 = suspended, 0 = new, 1 = learning, 2 = review, 3 = day learning, 4 = preview
+    #[serde(default)]
+    pub queue: i32,
+    /// New queue position, unix timestamp (learning) or day number (review)
+    #[serde(default)]
+    pub due: i64,
+    #[serde(default)]
+    pub interval: i64,
+    /// Ease in permille, e.g. 2500 for 250%
+    #[serde(default)]
+    pub factor: u32,
+    #[serde(default)]
+    pub reps: u32,
+    #[serde(default)]
+    pub lapses: u32,
+    #[serde(rename = "mod", default)]
+    pub modified: u64,
+}
+
+/// Fetches card details. IDs that no longer exist are skipped.
+pub fn cards_info(card_ids: &[u64]) -> Result<Vec<CardInfoDto>> {
+    if card_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // AnkiConnect answers unknown card IDs with an empty object
+    let raw: Vec<serde_json::Value> = invoke("cardsInfo", serde_json::json!({ "cards": card_ids }))?;
+
+    raw.into_iter()
+        .filter(|value| value.as_object().is_some_and(|card| !card.is_empty()))
+        .map(|value| serde_json::from_value(value)
+            .map_err(|e| anyhow!("Unexpected card in result of AnkiConnect action 'cardsInfo': {}", e)))
+        .collect()
+}
+
+pub fn find_cards(query: &str) -> Result<Vec<u64>> {
+    invoke("findCards", serde_json::json!({ "query": query }))
+}
+
+/// Moves cards to the given deck, creating the deck if it does not exist.
+pub fn change_deck(card_ids: &[u64], deck: &str) -> Result<()> {
+    invoke("changeDeck", serde_json::json!({ "cards": card_ids, "deck": deck }))
+}
+
+/// Returns the ID of the options preset used by a deck, or `None` for a filtered deck.
+pub fn deck_config_id(deck: &str) -> Result<Option<u64>> {
+    // AnkiConnect answers `false` instead of a config for filtered decks
+    let config: serde_json::Value = invoke("getDeckConfig", serde_json::json!({ "deck": deck }))?;
+    match config {
+        serde_json::Value::Bool(false) => Ok(None),
+        config => config.get("id")
+            .and_then(|id| id.as_u64())
+            .map(Some)
+            .ok_or_else(|| anyhow!("Deck config of '{}' has no ID", deck)),
+    }
+}
+
+/// Switches decks to the options preset with the given ID.
+pub fn set_deck_config_id(decks: &[&str], config_id: u64) -> Result<()> {
+    let updated: bool = invoke("setDeckConfigId", serde_json::json!({ "decks": decks, "configId": config_id }))?;
+    if !updated {
+        return Err(anyhow!("AnkiConnect could not apply options preset {}", config_id));
+    }
+    Ok(())
+}
+
+/// Quotes a deck name for use in a search query, escaping Anki's wildcards.
+pub fn deck_query(deck: &str) -> String {
+    let mut escaped = String::with_capacity(deck.len());
+    for c in deck.chars() {
+        if matches!(c, '\\' | '"' | '*' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    format!("deck:\"{}\"", escaped)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelFieldDto {
+    pub name: String,
+    pub ord: usize,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelTemplateDto {
+    pub name: String,
+    pub ord: usize,
+    pub qfmt: String,
+    pub afmt: String,
+}
+
+/// Full note type definition as returned by `findModelsByName`.
+#[derive(Debug, Deserialize)]
+pub struct ModelDto {
+    pub id: u64,
+    pub name: String,
+    /// 0 for standard note types, 1 for cloze
+    #[serde(rename = "type")]
+    pub kind: u8,
+    pub flds: Vec<ModelFieldDto>,
+    pub tmpls: Vec<ModelTemplateDto>,
+    pub css: String,
+    #[serde(default)]
+    pub sortf: usize,
+}
+
+pub fn model_names_and_ids() -> Result<HashMap<String, u64>> {
+    invoke("modelNamesAndIds", serde_json::json!({}))
+}
+
+pub fn find_models_by_name(names: &[String]) -> Result<Vec<ModelDto>> {
+    invoke("findModelsByName", serde_json::json!({ "modelNames": names }))
+}
+
+pub fn create_model(
+    name: &str,
+    fields: &[String],
+    css: &str,
+    is_cloze: bool,
+    templates: &[(String, String, String)],
+) -> Result<()> {
+    let card_templates: Vec<serde_json::Value> = templates
+        .iter()
+        .map(|(name, front, back)| serde_json::json!({ "Name": name, "Front": front, "Back": back }))
+        .collect();
+
+    // createModel returns the whole new model, which callers re-read anyway
+    invoke::<serde_json::Value>("createModel", serde_json::json!({
+        "modelName": name,
+        "inOrderFields": fields,
+        "css": css,
+        "isCloze": is_cloze,
+        "cardTemplates": card_templates,
+    }))?;
+
+    Ok(())
+}
+
+pub fn model_field_add(model: &str, field: &str, index: usize) -> Result<()> {
+    invoke("modelFieldAdd", serde_json::json!({ "modelName": model, "fieldName": field, "index": index }))
+}
+
+pub fn model_field_rename(model: &str, old_name: &str, new_name: &str) -> Result<()> {
+    invoke("modelFieldRename", serde_json::json!({
+        "modelName": model,
+        "oldFieldName": old_name,
+        "newFieldName": new_name,
+    }))
+}
+
+pub fn model_field_reposition(model: &str, field: &str, index: usize) -> Result<()> {
+    invoke("modelFieldReposition", serde_json::json!({ "modelName": model, "fieldName": field, "index": index }))
+}
+
+pub fn model_field_remove(model: &str, field: &str) -> Result<()> {
+    invoke("modelFieldRemove", serde_json::json!({ "modelName": model, "fieldName": field }))
+}
+
+pub fn model_template_add(model: &str, name: &str, front: &str, back: &str) -> Result<()> {
+    invoke("modelTemplateAdd", serde_json::json!({
+        "modelName": model,
+        "template": { "Name": name, "Front": front, "Back": back },
+    }))
+}
+
+pub fn update_model_templates(model: &str, templates: &[(String, String, String)]) -> Result<()> {
+    let templates: serde_json::Map<String, serde_json::Value> = templates
+        .iter()
+        .map(|(name, front, back)| (name.clone(), serde_json::json!({ "Front": front, "Back": back })))
+        .collect();
+
+    invoke("updateModelTemplates", serde_json::json!({
+        "model": { "name": model, "templates": templates },
+    }))
+}
+
+pub fn update_model_styling(model: &str, css: &str) -> Result<()> {
+    invoke("updateModelStyling", serde_json::json!({ "model": { "name": model, "css": css } }))
+}
+
+pub fn suspend(card_ids: &[u64]) -> Result<bool> {
+    invoke("suspend", serde_json::json!({ "cards": card_ids }))
+}
+
+pub fn unsuspend(card_ids: &[u64]) -> Result<bool> {
+    invoke("unsuspend", serde_json::json!({ "cards": card_ids }))
+}
+
+/// Resets cards to new.
+pub fn forget_cards(card_ids: &[u64]) -> Result<()> {
+    invoke("forgetCards", serde_json::json!({ "cards": card_ids }))
+}
+
+/// `days` uses Anki's "Set Due Date" syntax: "0" is today, "1-7" a random day in
+/// that range, and a trailing "!" also sets the interval to the chosen delay.
+pub fn set_due_date(card_ids: &[u64], days: &str) -> Result<bool> {
+    invoke("setDueDate", serde_json::json!({ "cards": card_ids, "days": days }))
+}
+
+/// Buries cards by moving them to Anki's "manually buried" queue (-3), in one
+/// `multi` request. AnkiConnect has no bury action, so this sets the raw `queue`
+/// column of each card the way Anki's own Bury does. The card type and
+/// scheduling are untouched, and Anki restores the queue when it unburies
+/// cards at the next day.
+pub fn bury(card_ids: &[u64]) -> Result<()> {
+    let actions: Vec<serde_json::Value> = card_ids
+        .iter()
+        .map(|id| serde_json::json!({
+            "action": "setSpecificValueOfCard",
+            "version": ANKICONNECT_API_VERSION,
+            "params": {
+                "card": id,
+                "keys": ["queue"],
+                "newValues": ["-3"],
+                "warning_check": true,
+            },
+        }))
+        .collect();
+
+    let results: Vec<AnkiConnectResponse> = invoke("multi", serde_json::json!({ "actions": actions }))?;
+    if let Some((id, error)) = card_ids.iter().zip(results).find_map(|(id, r)| r.error.map(|e| (id, e))) {
+        return Err(anyhow!("Failed to bury card {}: {}", id, error));
+    }
+
+    Ok(())
+}
+
+/// One entry of a card's review log.
+#[derive(Debug, Deserialize)]
+pub struct ReviewLogDto {
+    /// Review time in milliseconds since the epoch
+    pub id: u64,
+    /// Answer button: 1 = again, 2 = hard, 3 = good, 4 = easy (0 for manual changes)
+    pub ease: u8,
+    /// 0 = learning, 1 = review, 2 = relearning, 3 = filtered, 4 = manual
+    #[serde(rename = "type")]
+    pub review_type: u8,
+    /// Time spent answering in milliseconds
+    pub time: u64,
+}
+
+/// Fetches review logs keyed by card ID.
+pub fn reviews_of_cards(card_ids: &[u64]) -> Result<HashMap<u64, Vec<ReviewLogDto>>> {
+    if card_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let ids: Vec<String> = card_ids.iter().map(|id| id.to_string()).collect();
+    let raw: HashMap<String, Vec<ReviewLogDto>> = invoke("getReviewsOfCards", serde_json::json!({ "cards": ids }))?;
+
+    Ok(raw
+        .into_iter()
+        .filter_map(|(id, reviews)| id.parse().ok().map(|id| (id, reviews)))
+        .collect())
+}
+
+/// Returns the base64 content of a media file, or `None` if it does not exist.
+pub fn retrieve_media_file(filename: &str) -> Result<Option<String>> {
+    // AnkiConnect answers `false` for missing files
+    let result: serde_json::Value = invoke("retrieveMediaFile", serde_json::json!({ "filename": filename }))?;
+    Ok(result.as_str().map(|data| data.to_string()))
+}
+
+/// Lists media file names matching a glob pattern such as `*.mp3`.
+pub fn media_file_names(pattern: &str) -> Result<Vec<String>> {
+    invoke("getMediaFilesNames", serde_json::json!({ "pattern": pattern }))
+}
+
+pub fn delete_media_file(filename: &str) -> Result<()> {
+    invoke("deleteMediaFile", serde_json::json!({ "filename": filename }))
+}
@@ -0,0 +1,328 @@
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::protocol::*;
+use crate::server::anki_connect;
+use crate::server::anki_tools::{
+    deck_subtree, DeckStatistics, ListNoteTypesTool, ReviewStatsTool, REVIEW_STATS_MAX_DAYS,
+};
+use crate::server::capabilities::Prompt;
+use crate::server::context::RequestContext;
+
+const LEECHES_DEFAULT_LIMIT: usize = 20;
+const LEECHES_MAX_LIMIT: usize = 50;
+const STUDY_PROGRESS_DEFAULT_DAYS: u64 = 7;
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        required: Some(required),
+    }
+}
+
+fn user_prompt(description: String, text: String) -> GetPromptResult {
+    GetPromptResult {
+        description: Some(description),
+        messages: vec![PromptMessage {
+            role: "user".to_string(),
+            content: ToolResultContent::Text { text },
+        }],
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(arguments: &HashMap<String, String>, name: &str) -> Result<Option<T>> {
+    arguments.get(name)
+        .map(|v| v.trim().parse::<T>().map_err(|_| anyhow!("Invalid '{}' argument: expected a number", name)))
+        .transpose()
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value).map_err(|e| anyhow!("Failed to serialize prompt data: {}", e))
+}
+
+/// Turns a piece of text into cloze notes, using the collection's cloze note types and decks.
+pub struct MakeClozeCardsPrompt;
+
+#[async_trait]
+impl Prompt for MakeClozeCardsPrompt {
+    fn definition(&self, name: String) -> crate::protocol::Prompt {
+        crate::protocol::Prompt {
+            name,
+            description: Some("Make cloze cards from a piece of text, using your cloze note types and decks".to_string()),
+            arguments: Some(vec![
+                argument("text", "The text to turn into cloze cards", true),
+                argument("deck", "Deck to add the notes to (default: ask)", false),
+                argument("note_type", "Cloze note type to use (default: the first cloze note type)", false),
+            ]),
+        }
+    }
+
+    async fn get(&self, arguments: HashMap<String, String>) -> Result<GetPromptResult> {
+        let text = arguments.get("text")
+            .ok_or_else(|| anyhow!("Missing 'text' argument"))?;
+
+        let client = anki_connect::client();
+
+        let cloze_types: Vec<_> = ListNoteTypesTool.list_note_types(false).await?
+            .note_types
+            .into_iter()
+            .filter(|t| t.is_cloze)
+            .collect();
+
+        let note_type = match arguments.get("note_type") {
+            Some(name) => cloze_types.iter()
+                .find(|t| &t.name == name)
+                .ok_or_else(|| anyhow!("'{}' is not a cloze note type", name))?,
+            None => cloze_types.first()
+                .ok_or_else(|| anyhow!("The collection has no cloze note type; create one first"))?,
+        };
+        let fields: Vec<&str> = note_type.fields.iter().map(|f| f.name.as_str()).collect();
+
+        let mut decks: Vec<String> = client.decks().get_all()
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?
+            .iter()
+            .map(|d| d.name().to_string())
+            .collect();
+        decks.sort();
+
+        let deck_line = match arguments.get("deck") {
+            Some(deck) if decks.contains(deck) => format!("Deck: \"{}\"", deck),
+            Some(deck) => format!("Deck: \"{}\" (does not exist yet; create it with create_deck first)", deck),
+            None => "Deck: ask me which of the existing decks to use".to_string(),
+        };
+
+        let prompt = format!(
+            r#"Turn the text below into Anki cloze cards.
+
+Guidelines:
+- Write one note per fact or closely related group of facts, rephrasing sentences so each note stands on its own.
+- Use {{{{c1::answer}}}} deletions; use c2, c3, ... for deletions that should become separate cards, and {{{{c1::answer::hint}}}} when the answer would otherwise be ambiguous.
+- Only delete the key terms, names, numbers or ideas worth remembering, never filler words.
+- Put the cloze sentence in the "{text_field}" field and leave the other fields empty unless there is useful extra context.
+
+Target:
+- {deck_line}
+- Note type: "{note_type}" (fields: {fields})
+
+First show me the proposed notes as a list. After I confirm, create them in one call with the add_notes tool and report any duplicates or errors.
+
+Existing decks:
+{decks}
+
+Text:
+"""
+{text}
+""""#,
+            text_field = fields.first().copied().unwrap_or("Text"),
+            deck_line = deck_line,
+            note_type = note_type.name,
+            fields = fields.iter().map(|f| format!("\"{}\"", f)).collect::<Vec<_>>().join(", "),
+            decks = decks.iter().map(|d| format!("- {}", d)).collect::<Vec<_>>().join("\n"),
+            text = text,
+        );
+
+        Ok(user_prompt("Make cloze cards from text".to_string(), prompt))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeechInfo {
+    pub note_id: u64,
+    pub card_id: u64,
+    pub deck: String,
+    pub note_type: String,
+    pub lapses: u32,
+    pub reviews: u32,
+    /// Ease in percent
+    pub ease: u32,
+    pub interval_days: i64,
+    pub suspended: bool,
+    pub fields: HashMap<String, String>,
+}
+
+/// Reviews the notes Anki tagged as leeches, embedding their content and lapse history.
+pub struct ReviewLeechesPrompt;
+
+#[async_trait]
+impl Prompt for ReviewLeechesPrompt {
+    fn definition(&self, name: String) -> crate::protocol::Prompt {
+        crate::protocol::Prompt {
+            name,
+            description: Some("Review your leech cards: why they keep failing and how to rewrite, split or retire them".to_string()),
+            arguments: Some(vec![
+                argument("deck", "Only look at leeches in this deck and its subdecks", false),
+                argument("limit", "Maximum number of leeches to include (default: 20, max: 50)", false),
+            ]),
+        }
+    }
+
+    async fn get(&self, arguments: HashMap<String, String>) -> Result<GetPromptResult> {
+        let limit = parse_number::<usize>(&arguments, "limit")?
+            .unwrap_or(LEECHES_DEFAULT_LIMIT)
+            .clamp(1, LEECHES_MAX_LIMIT);
+
+        let deck = arguments.get("deck");
+        let query = match deck {
+            Some(deck) => format!("tag:leech {}", anki_connect::deck_query(deck)),
+            None => "tag:leech".to_string(),
+        };
+
+        let card_ids = anki_connect::find_cards(&query)
+            .map_err(|e| anyhow!("Failed to search cards: {}", e))?;
+
+        let mut cards = anki_connect::cards_info(&card_ids)
+            .map_err(|e| anyhow!("Failed to retrieve card details: {}", e))?;
+        cards.sort_by(|a, b| b.lapses.cmp(&a.lapses).then(a.card_id.cmp(&b.card_id)));
+        cards.truncate(limit);
+
+        let mut note_ids: Vec<u64> = cards.iter().map(|c| c.note).collect();
+        note_ids.sort_unstable();
+        note_ids.dedup();
+
+        let notes: HashMap<u64, anki_connect::NoteInfoDto> = anki_connect::notes_info(&note_ids)
+            .map_err(|e| anyhow!("Failed to retrieve note details: {}", e))?
+            .into_iter()
+            .map(|n| (n.note_id, n))
+            .collect();
+
+        let leeches: Vec<LeechInfo> = cards
+            .iter()
+            .map(|card| LeechInfo {
+                note_id: card.note,
+                card_id: card.card_id,
+                deck: card.deck_name.clone(),
+                note_type: card.model_name.clone(),
+                lapses: card.lapses,
+                reviews: card.reps,
+                ease: card.factor / 10,
+                interval_days: card.interval,
+                suspended: card.queue == -1,
+                fields: notes.get(&card.note)
+                    .map(|n| n.fields.iter().map(|(name, f)| (name.clone(), f.value.clone())).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let scope = deck.map(|d| format!("deck \"{}\"", d)).unwrap_or_else(|| "my collection".to_string());
+
+        let prompt = if leeches.is_empty() {
+            format!(
+                "Anki has not tagged any cards in {} as leeches. Tell me so, and suggest looking at cards with many lapses instead (for example with the get_cards tool and the query \"prop:lapses>=4\").",
+                scope
+            )
+        } else {
+            format!(
+                r#"Help me deal with my leeches: cards in {scope} that I keep forgetting. Showing {shown} of {total}, most lapses first.
+
+For each leech:
+1. Diagnose why it keeps failing (too much information, ambiguous question, interference with a similar card, missing context, no memory hook).
+2. Propose a concrete fix: rewritten fields, a split into several notes, a mnemonic, or deleting it if it is not worth learning.
+
+Then summarize the common patterns you see. Do not change anything yet. After I approve a fix, apply it with update_note_fields, remove the "leech" tag with update_note_tags and, if the card is suspended, unsuspend it with unsuspend_cards.
+
+Leeches (ease in percent, interval in days):
+{leeches}"#,
+                scope = scope,
+                shown = leeches.len(),
+                total = card_ids.len(),
+                leeches = to_json(&leeches)?,
+            )
+        };
+
+        Ok(user_prompt(format!("Review leeches in {}", scope), prompt))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeckProgress {
+    pub name: String,
+    pub due_today: DeckStatistics,
+}
+
+/// Summarizes recent study progress from review statistics and today's due counts.
+pub struct StudyProgressPrompt;
+
+#[async_trait]
+impl Prompt for StudyProgressPrompt {
+    fn definition(&self, name: String) -> crate::protocol::Prompt {
+        crate::protocol::Prompt {
+            name,
+            description: Some("Summarize your recent study progress: reviews, retention, workload and what is coming up".to_string()),
+            arguments: Some(vec![
+                argument("deck", "Only summarize this deck and its subdecks (default: whole collection)", false),
+                argument("days", "Number of past days to summarize (default: 7, at most 3650)", false),
+            ]),
+        }
+    }
+
+    async fn get(&self, arguments: HashMap<String, String>) -> Result<GetPromptResult> {
+        let days = parse_number::<u64>(&arguments, "days")?
+            .unwrap_or(STUDY_PROGRESS_DEFAULT_DAYS)
+            .clamp(1, REVIEW_STATS_MAX_DAYS);
+
+        let client = anki_connect::client();
+
+        let deck = arguments.get("deck");
+        let query = match deck {
+            Some(deck) => anki_connect::deck_query(deck),
+            None => "deck:*".to_string(),
+        };
+
+        let all_decks = client.decks().get_all()
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?;
+        let decks = match deck {
+            Some(deck) => deck_subtree(&all_decks, deck),
+            None => all_decks.iter().collect(),
+        };
+        if decks.is_empty() {
+            return Err(anyhow!("Deck '{}' not found", deck.map(String::as_str).unwrap_or_default()));
+        }
+
+        let stats = ReviewStatsTool.review_stats(query, days, STUDY_PROGRESS_DEFAULT_DAYS, &RequestContext::default()).await?;
+
+        let mut deck_progress = Vec::new();
+        for d in decks {
+            match client.decks().get_stat(d.name()) {
+                Ok(s) => deck_progress.push(DeckProgress {
+                    name: d.name().to_string(),
+                    due_today: DeckStatistics {
+                        new_count: s.new_count,
+                        learn_count: s.learn_count,
+                        review_count: s.review_count,
+                        total_in_deck: s.total_in_deck,
+                    },
+                }),
+                Err(e) => tracing::warn!("Failed to get statistics for deck '{}': {}", d.name(), e),
+            }
+        }
+        deck_progress.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let scope = deck.map(|d| format!("deck \"{}\"", d)).unwrap_or_else(|| "my whole collection".to_string());
+
+        let prompt = format!(
+            r#"Summarize my Anki study progress in {scope} over the last {days} day(s), based on the data below.
+
+Cover:
+- How much I studied and how consistent I was day to day (daily_reviews, days_ago 0 = the last 24 hours).
+- How well I remember: retention rate (around 85-90% is healthy), lapse rate and answer time.
+- Today's workload per deck and the due forecast for the coming week, calling out heavy days.
+- Two or three concrete suggestions, e.g. adjusting new cards per day or tackling leeches with the review_leeches prompt.
+
+Keep it short and encouraging, and use numbers from the data rather than general advice.
+
+Review statistics:
+{stats}
+
+Due today per deck:
+{decks}"#,
+            scope = scope,
+            days = days,
+            stats = to_json(&stats)?,
+            decks = to_json(&deck_progress)?,
+        );
+
+        Ok(user_prompt(format!("Study progress for {}", scope), prompt))
+    }
+}
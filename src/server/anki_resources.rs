@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::protocol::*;
+use crate::server::anki_connect;
+use crate::server::anki_tools::{deck_subtree, fetch_note, DeckStatistics, NoteDetails};
+use crate::server::capabilities::ResourceTemplate;
+
+fn json_contents<T: Serialize>(uri: &str, value: &T) -> Result<Vec<ResourceContent>> {
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|e| anyhow!("Failed to serialize resource: {}", e))?;
+
+    Ok(vec![ResourceContent::Text {
+        uri: uri.to_string(),
+        text,
+        mime_type: Some("application/json".to_string()),
+    }])
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeckResourceContent {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<DeckStatistics>,
+    /// Counts include the cards and notes of all subdecks.
+    pub card_count: usize,
+    pub note_count: usize,
+    pub subdecks: Vec<String>,
+}
+
+/// Serves `anki://deck/{name}`: a deck's statistics, counts and subdecks.
+pub struct DeckResource;
+
+#[async_trait]
+impl ResourceTemplate for DeckResource {
+    fn definition(&self, uri_template: String) -> crate::protocol::ResourceTemplate {
+        crate::protocol::ResourceTemplate {
+            uri_template,
+            name: "deck".to_string(),
+            description: Some("An Anki deck by full name (e.g. anki://deck/Japanese::Grammar, percent-encoded if needed) with statistics, card and note counts and subdecks".to_string()),
+            mime_type: Some("application/json".to_string()),
+        }
+    }
+
+    async fn read(&self, uri: &str, params: HashMap<String, String>) -> Result<Vec<ResourceContent>> {
+        let name = params.get("name")
+            .ok_or_else(|| anyhow!("Missing deck name in '{}'", uri))?;
+
+        let client = anki_connect::client();
+
+        let decks = client.decks().get_all()
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?;
+
+        let deck = decks.iter()
+            .find(|d| d.name() == name)
+            .ok_or_else(|| anyhow!("Deck '{}' not found", name))?;
+
+        let statistics = match client.decks().get_stat(name) {
+            Ok(stats) => Some(DeckStatistics {
+                new_count: stats.new_count,
+                learn_count: stats.learn_count,
+                review_count: stats.review_count,
+                total_in_deck: stats.total_in_deck,
+            }),
+            Err(e) => {
+                tracing::warn!("Failed to get statistics for deck '{}': {}", name, e);
+                None
+            }
+        };
+
+        let query = anki_connect::deck_query(name);
+        let card_count = anki_connect::find_cards(&query)
+            .map_err(|e| anyhow!("Failed to search cards in deck '{}': {}", name, e))?
+            .len();
+        let note_count = anki_connect::find_notes(&query)
+            .map_err(|e| anyhow!("Failed to search notes in deck '{}': {}", name, e))?
+            .len();
+
+        let subdecks = deck_subtree(&decks, name)
+            .into_iter()
+            .filter(|d| d.name() != name)
+            .map(|d| d.name().to_string())
+            .collect();
+
+        json_contents(uri, &DeckResourceContent {
+            id: deck.id().0.to_string(),
+            name: name.clone(),
+            statistics,
+            card_count,
+            note_count,
+            subdecks,
+        })
+    }
+}
+
+/// Serves `anki://note/{id}`: a note's fields, tags and cards.
+pub struct NoteResource;
+
+#[async_trait]
+impl ResourceTemplate for NoteResource {
+    fn definition(&self, uri_template: String) -> crate::protocol::ResourceTemplate {
+        crate::protocol::ResourceTemplate {
+            uri_template,
+            name: "note".to_string(),
+            description: Some("An Anki note by ID with its note type, fields in order, tags and card IDs".to_string()),
+            mime_type: Some("application/json".to_string()),
+        }
+    }
+
+    async fn read(&self, uri: &str, params: HashMap<String, String>) -> Result<Vec<ResourceContent>> {
+        let note_id: u64 = params.get("id")
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| anyhow!("Invalid note ID in '{}'", uri))?;
+
+        let note = fetch_note(note_id)?;
+        json_contents(uri, &NoteDetails::from(&note))
+    }
+}
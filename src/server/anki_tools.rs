@@ -1,16 +1,23 @@
 use async_trait::async_trait;
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use ankiconnect_rs::{AnkiClient, Deck};
+use ankiconnect_rs::client::request::DeckTreeNode;
+use ankiconnect_rs::{AnkiClient, AnkiConnectError, AnkiError, Deck, DuplicateScope, Model, Note, NoteBuilder, NoteId};
 use crate::protocol::*;
-use crate::server::capabilities::Tool;
+use crate::server::anki_connect;
+use crate::server::capabilities::{NoArguments, ToolMetadata, TypedTool};
+use crate::server::context::RequestContext;
+use crate::server::resources::percent_decode;
+use crate::server::schema::{schema_for, structured_result, FieldError, InvalidArguments};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeckInfo {
     pub id: String,
     pub name: String,
-    pub deck_type: String, // "root" or "subdeck"
+    /// "root" or "subdeck"
+    pub deck_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_name: Option<String>,
     pub base_name: String,
@@ -21,7 +28,7 @@ pub struct DeckInfo {
     pub cards_available: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeckStatistics {
     pub new_count: u32,
     pub learn_count: u32,
@@ -29,14 +36,24 @@ pub struct DeckStatistics {
     pub total_in_deck: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeckHierarchyNode {
     pub name: String,
     pub id: String,
     pub children: Vec<DeckHierarchyNode>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl From<&DeckTreeNode> for DeckHierarchyNode {
+    fn from(node: &DeckTreeNode) -> Self {
+        DeckHierarchyNode {
+            name: node.name.clone(),
+            id: node.id.to_string(),
+            children: node.children.iter().map(DeckHierarchyNode::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ConnectionInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ankiconnect_version: Option<String>,
@@ -45,7 +62,7 @@ pub struct ConnectionInfo {
     pub connection_successful: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ListDecksResponse {
     pub decks: Vec<DeckInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -69,17 +86,8 @@ impl ListDecksTool {
         }
     }
 
-    fn convert_tree_node(&self, node: &ankiconnect_rs::client::request::DeckTreeNode) -> DeckHierarchyNode {
-        DeckHierarchyNode {
-            name: node.name.clone(),
-            id: node.id.to_string(),
-            children: node.children.iter().map(|child| self.convert_tree_node(child)).collect(),
-        }
-    }
-
-    async fn fetch_deck_data(&self) -> Result<ListDecksResponse> {
-        // Create AnkiConnect client
-        let client = AnkiClient::new();
+    async fn fetch_deck_data(&self, context: &RequestContext) -> Result<ListDecksResponse> {
+        let client = anki_connect::client();
         
         // Check connection and version
         let version = client.version().map_err(|e| {
@@ -96,7 +104,7 @@ impl ListDecksTool {
         let mut deck_infos = Vec::new();
 
         // Process each deck
-        for deck in &decks {
+        for (index, deck) in decks.iter().enumerate() {
             let mut deck_info = self.convert_deck(deck);
 
             // Try to get statistics for this deck
@@ -127,11 +135,12 @@ impl ListDecksTool {
             }
 
             deck_infos.push(deck_info);
+            context.report_progress(index + 1, decks.len(), format!("Fetched deck '{}'", deck.name()));
         }
 
         // Try to get deck hierarchy
         let hierarchy = match client.decks().get_tree() {
-            Ok(tree) => Some(tree.iter().map(|node| self.convert_tree_node(node)).collect()),
+            Ok(tree) => Some(tree.iter().map(DeckHierarchyNode::from).collect()),
             Err(e) => {
                 tracing::warn!("Failed to get deck hierarchy: {}", e);
                 None
@@ -157,45 +166,3234 @@ impl ListDecksTool {
 }
 
 #[async_trait]
-impl Tool for ListDecksTool {
-    fn definition(&self, name: String) -> crate::protocol::Tool {
-        crate::protocol::Tool {
-            name,
+impl TypedTool for ListDecksTool {
+    type Args = NoArguments;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
             description: Some("Lists all Anki decks with statistics, hierarchy, and card information".to_string()),
-            input_schema: Some(serde_json::json!({
-                "type": "object",
-                "properties": {},
-                "required": []
-            })),
+            output_schema: Some(schema_for::<ListDecksResponse>()),
+            annotations: Some(ToolAnnotations::read_only()),
         }
     }
 
-    async fn call(&self, _arguments: HashMap<String, serde_json::Value>) -> Result<CallToolResult> {
-        match self.fetch_deck_data().await {
-            Ok(response) => {
-                let json_response = serde_json::to_string_pretty(&response)
-                    .map_err(|e| anyhow!("Failed to serialize response: {}", e))?;
-
-                Ok(CallToolResult {
-                    content: vec![ToolResultContent::Text {
-                        text: json_response,
-                    }],
-                    is_error: Some(false),
-                })
-            }
+    async fn call(&self, _args: NoArguments, context: &RequestContext) -> Result<CallToolResult> {
+        match self.fetch_deck_data(context).await {
+            Ok(response) => structured_result(&response),
             Err(e) => {
                 let error_message = format!(
-                    "Error connecting to Anki: {}\n\nTroubleshooting:\n1. Ensure Anki is running\n2. Install AnkiConnect plugin (code: 2055492159)\n3. Verify AnkiConnect is accessible on localhost:8765\n4. Restart Anki if the plugin was just installed",
-                    e
+                    "Error connecting to Anki: {}\n\nTroubleshooting:\n1. Ensure Anki is running\n2. Install AnkiConnect plugin (code: 2055492159)\n3. Verify AnkiConnect is accessible on {}\n4. Restart Anki if the plugin was just installed",
+                    e,
+                    anki_connect::address()
                 );
 
-                Ok(CallToolResult {
-                    content: vec![ToolResultContent::Text {
-                        text: error_message,
-                    }],
-                    is_error: Some(true),
-                })
+                Ok(error_result(error_message))
+            }
+        }
+    }
+}
+
+const FIND_NOTES_DEFAULT_LIMIT: usize = 50;
+const FIND_NOTES_MAX_LIMIT: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct NoteField {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct NoteDetails {
+    pub note_id: u64,
+    pub model: String,
+    pub fields: Vec<NoteField>,
+    pub tags: Vec<String>,
+    pub card_ids: Vec<u64>,
+}
+
+impl From<&anki_connect::NoteInfoDto> for NoteDetails {
+    fn from(info: &anki_connect::NoteInfoDto) -> Self {
+        NoteDetails {
+            note_id: info.note_id,
+            model: info.model_name.clone(),
+            fields: info.ordered_fields()
+                .into_iter()
+                .map(|(name, value)| NoteField { name: name.to_string(), value: value.to_string() })
+                .collect(),
+            tags: info.tags.clone(),
+            card_ids: info.cards.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindNotesResponse {
+    pub query: String,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub has_more: bool,
+    pub notes: Vec<NoteDetails>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FindNotesArgs {
+    /// Anki search query, same syntax as the browser search bar
+    pub query: String,
+    /// Number of matching notes to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of notes to return
+    #[serde(default = "find_notes_default_limit")]
+    #[schemars(range(min = 1, max = FIND_NOTES_MAX_LIMIT))]
+    pub limit: usize,
+}
+
+fn find_notes_default_limit() -> usize {
+    FIND_NOTES_DEFAULT_LIMIT
+}
+
+pub struct FindNotesTool;
+
+impl FindNotesTool {
+    async fn find_notes(&self, query: &str, offset: usize, limit: usize) -> Result<FindNotesResponse> {
+        let mut note_ids = anki_connect::find_notes(query)
+            .map_err(|e| anyhow!("Failed to search notes: {}", e))?;
+
+        // Note IDs are creation timestamps, so sorting keeps pages stable between calls
+        note_ids.sort_unstable();
+
+        let total = note_ids.len();
+        let page: Vec<u64> = note_ids.into_iter().skip(offset).take(limit).collect();
+
+        let notes = anki_connect::notes_info(&page)
+            .map_err(|e| anyhow!("Failed to retrieve note details: {}", e))?
+            .iter()
+            .map(NoteDetails::from)
+            .collect();
+
+        Ok(FindNotesResponse {
+            query: query.to_string(),
+            total,
+            offset,
+            limit,
+            has_more: offset + page.len() < total,
+            notes,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for FindNotesTool {
+    type Args = FindNotesArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Searches notes using Anki search syntax (e.g. deck:\"Japanese\" tag:verb is:due) and returns their fields, tags, note type and card IDs, one page at a time".to_string()),
+            output_schema: Some(schema_for::<FindNotesResponse>()),
+            annotations: Some(ToolAnnotations::read_only()),
+        }
+    }
+
+    async fn call(&self, args: FindNotesArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        structured_json_result(self.find_notes(&args.query, args.offset, args.limit).await, "finding notes")
+    }
+}
+
+/// Turns a tool's outcome into its result: the response as pretty-printed JSON,
+/// or the error as text saying what the tool was doing, e.g. "adding note".
+/// [`InvalidArguments`] stay errors, so the client gets `invalid_params`.
+pub(crate) fn json_result<T: Serialize>(result: Result<T>, action: &str) -> Result<CallToolResult> {
+    match result {
+        Ok(response) => {
+            let json_response = serde_json::to_string_pretty(&response)
+                .map_err(|e| anyhow!("Failed to serialize response: {}", e))?;
+
+            Ok(CallToolResult {
+                content: vec![ToolResultContent::Text {
+                    text: json_response,
+                }],
+                is_error: Some(false),
+                structured_content: None,
+            })
+        }
+        Err(e) if e.is::<InvalidArguments>() => Err(e),
+        Err(e) => Ok(error_result(format!("Error {}: {}", action, e))),
+    }
+}
+
+/// Like [`json_result`], but also returns the response as `structuredContent`.
+pub(crate) fn structured_json_result<T: Serialize + JsonSchema + 'static>(result: Result<T>, action: &str) -> Result<CallToolResult> {
+    match result {
+        Ok(response) => structured_result(&response),
+        Err(e) if e.is::<InvalidArguments>() => Err(e),
+        Err(e) => Ok(error_result(format!("Error {}: {}", action, e))),
+    }
+}
+
+pub(crate) fn error_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![ToolResultContent::Text { text }],
+        is_error: Some(true),
+        structured_content: None,
+    }
+}
+
+/// Fetches a single note, failing with a readable error if it does not exist.
+pub(crate) fn fetch_note(note_id: u64) -> Result<anki_connect::NoteInfoDto> {
+    anki_connect::notes_info(&[note_id])
+        .map_err(|e| anyhow!("Failed to retrieve note {}: {}", note_id, e))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Note {} not found", note_id))
+}
+
+/// Where `add_note` and `add_notes` look for notes with the same first field.
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateScopeArg {
+    Deck,
+    Collection,
+}
+
+impl From<DuplicateScopeArg> for DuplicateScope {
+    fn from(scope: DuplicateScopeArg) -> Self {
+        match scope {
+            DuplicateScopeArg::Deck => DuplicateScope::Deck,
+            DuplicateScopeArg::Collection => DuplicateScope::Collection,
+        }
+    }
+}
+
+/// Checks that every field name exists on the note type and that the note is not empty.
+fn check_note_fields(model: &Model, fields: &HashMap<String, String>) -> Result<()> {
+    let unknown: Vec<&str> = fields
+        .keys()
+        .filter(|name| model.get_field(name).is_none())
+        .map(|name| name.as_str())
+        .collect();
+
+    if !unknown.is_empty() {
+        let valid: Vec<&str> = model.fields().iter().map(|f| f.name()).collect();
+        return Err(anyhow!(
+            "Unknown field(s) {} for note type '{}'. Valid fields are: {}",
+            unknown.join(", "),
+            model.name(),
+            valid.join(", ")
+        ));
+    }
+
+    if fields.values().all(|value| value.trim().is_empty()) {
+        return Err(anyhow!("At least one field must have non-empty content"));
+    }
+
+    Ok(())
+}
+
+/// Builds a note after checking its fields against the note type.
+fn build_note(model: Model, fields: &HashMap<String, String>, tags: &[String]) -> Result<Note> {
+    check_note_fields(&model, fields)?;
+
+    let mut builder = NoteBuilder::new(model.clone());
+    for (name, value) in fields {
+        if let Some(field) = model.field_ref(name) {
+            builder = builder.with_field_raw(field, value);
+        }
+    }
+    for tag in tags {
+        builder = builder.with_tag(tag);
+    }
+
+    builder.build().map_err(|e| anyhow!("Invalid note: {}", e))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddNoteResponse {
+    pub note_id: u64,
+    pub deck: String,
+    pub model: String,
+    pub fields: HashMap<String, String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AddNoteArgs {
+    /// Name of the deck to add the note to, e.g. "Japanese::Vocab"
+    pub deck: String,
+    /// Name of the note type (model), e.g. "Basic"
+    pub model: String,
+    /// Map of field name to field content (HTML allowed)
+    pub fields: HashMap<String, String>,
+    /// Tags to attach to the note
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Add the note even if its first field duplicates an existing note (default: false)
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    /// Where to look for duplicates (default: collection)
+    pub duplicate_scope: Option<DuplicateScopeArg>,
+}
+
+pub struct AddNoteTool;
+
+impl AddNoteTool {
+    async fn add_note(
+        &self,
+        deck_name: &str,
+        model_name: &str,
+        fields: HashMap<String, String>,
+        tags: Vec<String>,
+        allow_duplicate: bool,
+        duplicate_scope: Option<DuplicateScope>,
+    ) -> Result<AddNoteResponse> {
+        let client = anki_connect::client();
+
+        let deck = client.decks().get_by_name(deck_name)
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?
+            .ok_or_else(|| anyhow!("Deck '{}' not found", deck_name))?;
+
+        let model = client.models().get_by_name(model_name)
+            .map_err(|e| anyhow!("Failed to retrieve note types from Anki: {}", e))?
+            .ok_or_else(|| anyhow!("Note type '{}' not found", model_name))?;
+
+        let note = build_note(model, &fields, &tags)?;
+
+        let note_id = client.cards().add_note(&deck, note, allow_duplicate, duplicate_scope)
+            .map_err(|e| match e {
+                AnkiError::AnkiConnectError(AnkiConnectError::DuplicateNote) => {
+                    anyhow!("Note is a duplicate of an existing note. Set 'allow_duplicate' to true to add it anyway")
+                }
+                e => anyhow!("Failed to add note: {}", e),
+            })?;
+
+        tracing::info!("Added note {} to deck '{}'", note_id.value(), deck_name);
+
+        Ok(AddNoteResponse {
+            note_id: note_id.value(),
+            deck: deck_name.to_string(),
+            model: model_name.to_string(),
+            fields,
+            tags,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for AddNoteTool {
+    type Args = AddNoteArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Creates a new note in an Anki deck and returns its note ID. Field names must match the note type's fields".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::non_destructive()),
+        }
+    }
+
+    async fn call(&self, args: AddNoteArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let duplicate_scope = args.duplicate_scope.map(DuplicateScope::from);
+        json_result(
+            self.add_note(&args.deck, &args.model, args.fields, args.tags, args.allow_duplicate, duplicate_scope).await,
+            "adding note",
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NoteSpec {
+    /// Name of the deck to add the note to
+    pub deck: String,
+    /// Name of the note type (model)
+    pub model: String,
+    /// Map of field name to field content (HTML allowed)
+    pub fields: HashMap<String, String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AddNoteOutcome {
+    Added { note_id: u64 },
+    Duplicate,
+    Error { message: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddNotesItemResult {
+    pub index: usize,
+    #[serde(flatten)]
+    pub outcome: AddNoteOutcome,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddNotesResponse {
+    pub results: Vec<AddNotesItemResult>,
+    pub added: usize,
+    pub duplicates: usize,
+    pub errors: usize,
+    /// Set when the client cancelled the call; `results` then covers only the
+    /// notes handled before the cancellation.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+}
+
+/// Notes sent to AnkiConnect per request; cancellation and progress are
+/// checked between chunks.
+const ADD_NOTES_CHUNK: usize = 100;
+
+pub struct AddNotesTool;
+
+impl AddNotesTool {
+    /// Checks a note spec against the collection and turns it into the note
+    /// object AnkiConnect's `addNotes` expects.
+    fn prepare(
+        &self,
+        decks: &[Deck],
+        models: &[Model],
+        spec: NoteSpec,
+        options: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        if !decks.iter().any(|d| d.name() == spec.deck) {
+            return Err(anyhow!("Deck '{}' not found", spec.deck));
+        }
+
+        let model = models.iter()
+            .find(|m| m.name() == spec.model)
+            .ok_or_else(|| anyhow!("Note type '{}' not found", spec.model))?;
+
+        check_note_fields(model, &spec.fields)?;
+
+        Ok(serde_json::json!({
+            "deckName": spec.deck,
+            "modelName": spec.model,
+            "fields": spec.fields,
+            "tags": spec.tags,
+            "options": options,
+        }))
+    }
+
+    /// Adds one chunk of prepared notes: `canAddNotesWithErrorDetail` sorts out
+    /// duplicates and other rejects, then the rest go in a single `addNotes` call.
+    fn add_chunk(&self, notes: &[serde_json::Value]) -> Vec<AddNoteOutcome> {
+        let checks = match anki_connect::can_add_notes_with_error_detail(notes) {
+            Ok(checks) if checks.len() == notes.len() => checks,
+            Ok(_) => return chunk_error(notes.len(), "AnkiConnect returned the wrong number of checks"),
+            Err(e) => return chunk_error(notes.len(), &format!("Failed to check notes: {}", e)),
+        };
+
+        let addable: Vec<serde_json::Value> = notes.iter()
+            .zip(&checks)
+            .filter(|(_, check)| check.can_add)
+            .map(|(note, _)| note.clone())
+            .collect();
+
+        let mut note_ids = match anki_connect::add_notes(&addable) {
+            Ok(ids) if ids.len() == addable.len() => ids.into_iter(),
+            Ok(_) => return chunk_error(notes.len(), "AnkiConnect returned the wrong number of note IDs"),
+            // Newer AnkiConnect versions fail the whole call if any note fails,
+            // after adding the others, so the outcome of this chunk is unknown
+            Err(e) => return chunk_error(notes.len(), &format!("Failed to add notes, some of this batch may have been added: {}", e)),
+        };
+
+        checks
+            .into_iter()
+            .map(|check| match check {
+                anki_connect::CanAddNoteDto { can_add: true, .. } => match note_ids.next().flatten() {
+                    Some(note_id) => AddNoteOutcome::Added { note_id },
+                    None => AddNoteOutcome::Error { message: "AnkiConnect did not add the note".to_string() },
+                },
+                anki_connect::CanAddNoteDto { error: Some(error), .. } if error.contains("duplicate") => AddNoteOutcome::Duplicate,
+                anki_connect::CanAddNoteDto { error, .. } => AddNoteOutcome::Error {
+                    message: format!("Failed to add note: {}", error.unwrap_or_else(|| "rejected by Anki".to_string())),
+                },
+            })
+            .collect()
+    }
+
+    async fn add_notes(
+        &self,
+        notes: Vec<NoteSpec>,
+        allow_duplicate: bool,
+        duplicate_scope: Option<DuplicateScope>,
+        context: &RequestContext,
+    ) -> Result<AddNotesResponse> {
+        let client = anki_connect::client();
+
+        // Look decks and note types up once for the whole batch instead of once per note
+        let decks = client.decks().get_all()
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?;
+
+        let models = client.models().get_all()
+            .map_err(|e| anyhow!("Failed to retrieve note types from Anki: {}", e))?;
+
+        let mut options = serde_json::json!({ "allowDuplicate": allow_duplicate });
+        if let Some(scope) = duplicate_scope {
+            options["duplicateScope"] = serde_json::json!(match scope {
+                DuplicateScope::Deck => "deck",
+                DuplicateScope::Collection => "collection",
+            });
+        }
+
+        let mut response = AddNotesResponse {
+            results: Vec::with_capacity(notes.len()),
+            added: 0,
+            duplicates: 0,
+            errors: 0,
+            cancelled: false,
+        };
+
+        let total = notes.len();
+        let mut notes = notes.into_iter().enumerate().peekable();
+        while notes.peek().is_some() {
+            if context.is_cancelled() {
+                response.cancelled = true;
+                break;
+            }
+
+            let mut outcomes: Vec<(usize, Option<AddNoteOutcome>)> = Vec::new();
+            let mut prepared = Vec::new();
+            for (index, spec) in notes.by_ref().take(ADD_NOTES_CHUNK) {
+                match self.prepare(&decks, &models, spec, &options) {
+                    Ok(note) => {
+                        prepared.push(note);
+                        outcomes.push((index, None));
+                    }
+                    Err(e) => outcomes.push((index, Some(AddNoteOutcome::Error { message: e.to_string() }))),
+                }
+            }
+
+            let mut added = self.add_chunk(&prepared).into_iter();
+            for (index, outcome) in outcomes {
+                let outcome = outcome.or_else(|| added.next()).unwrap_or(AddNoteOutcome::Error {
+                    message: "Note was not processed".to_string(),
+                });
+
+                match &outcome {
+                    AddNoteOutcome::Added { .. } => response.added += 1,
+                    AddNoteOutcome::Duplicate => response.duplicates += 1,
+                    AddNoteOutcome::Error { message } => {
+                        tracing::warn!("Failed to add note at index {}: {}", index, message);
+                        response.errors += 1;
+                    }
+                }
+
+                response.results.push(AddNotesItemResult { index, outcome });
             }
+
+            let done = response.results.len();
+            context.report_progress(done, total, format!("Processed {} of {} notes", done, total));
+        }
+
+        tracing::info!(
+            "Bulk add {}: {} added, {} duplicates, {} errors",
+            if response.cancelled { "cancelled" } else { "finished" },
+            response.added,
+            response.duplicates,
+            response.errors
+        );
+
+        Ok(response)
+    }
+}
+
+fn chunk_error(count: usize, message: &str) -> Vec<AddNoteOutcome> {
+    (0..count)
+        .map(|_| AddNoteOutcome::Error { message: message.to_string() })
+        .collect()
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AddNotesArgs {
+    /// Notes to create
+    pub notes: Vec<NoteSpec>,
+    /// Add notes even if their first field duplicates an existing note (default: false)
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    /// Where to look for duplicates (default: collection)
+    pub duplicate_scope: Option<DuplicateScopeArg>,
+}
+
+#[async_trait]
+impl TypedTool for AddNotesTool {
+    type Args = AddNotesArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Creates many notes in one call. Returns a per-note result (added with note ID, duplicate, or error) in the same order as the input, so one bad note does not fail the batch".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::non_destructive()),
+        }
+    }
+
+    async fn call(&self, args: AddNotesArgs, context: &RequestContext) -> Result<CallToolResult> {
+        let duplicate_scope = args.duplicate_scope.map(DuplicateScope::from);
+        json_result(self.add_notes(args.notes, args.allow_duplicate, duplicate_scope, context).await, "adding notes")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateNoteFieldsResponse {
+    pub note_id: u64,
+    pub model: String,
+    pub changes: Vec<FieldChange>,
+    pub fields: Vec<NoteField>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateNoteFieldsArgs {
+    /// ID of the note to update
+    pub note_id: u64,
+    /// Map of field name to new content (HTML allowed). Fields not listed keep their current content
+    pub fields: HashMap<String, String>,
+}
+
+pub struct UpdateNoteFieldsTool;
+
+impl UpdateNoteFieldsTool {
+    async fn update_fields(&self, note_id: u64, fields: HashMap<String, String>) -> Result<UpdateNoteFieldsResponse> {
+        let before = fetch_note(note_id)?;
+
+        let unknown: Vec<&str> = fields
+            .keys()
+            .filter(|name| !before.fields.contains_key(*name))
+            .map(|name| name.as_str())
+            .collect();
+
+        if !unknown.is_empty() {
+            let valid: Vec<&str> = before.ordered_fields().into_iter().map(|(name, _)| name).collect();
+            return Err(anyhow!(
+                "Unknown field(s) {} for note type '{}'. Valid fields are: {}",
+                unknown.join(", "),
+                before.model_name,
+                valid.join(", ")
+            ));
+        }
+
+        // Only send fields whose content actually changes
+        let changed: HashMap<String, String> = fields
+            .into_iter()
+            .filter(|(name, value)| before.fields[name].value != *value)
+            .collect();
+
+        if !changed.is_empty() {
+            anki_connect::update_note_fields(note_id, &changed)
+                .map_err(|e| anyhow!("Failed to update note {}: {}", note_id, e))?;
+            tracing::info!("Updated {} field(s) of note {}", changed.len(), note_id);
+        }
+
+        let after = fetch_note(note_id)?;
+
+        let changes = before.ordered_fields()
+            .into_iter()
+            .filter(|(name, _)| changed.contains_key(*name))
+            .map(|(name, old)| FieldChange {
+                field: name.to_string(),
+                before: old.to_string(),
+                after: after.fields.get(name).map(|f| f.value.clone()).unwrap_or_default(),
+            })
+            .collect();
+
+        let details = NoteDetails::from(&after);
+
+        Ok(UpdateNoteFieldsResponse {
+            note_id,
+            model: details.model,
+            changes,
+            fields: details.fields,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for UpdateNoteFieldsTool {
+    type Args = UpdateNoteFieldsArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Updates some fields of an existing note, leaving the others untouched. Returns the before/after value of every changed field".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: UpdateNoteFieldsArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        json_result(self.update_fields(args.note_id, args.fields).await, "updating note fields")
+    }
+}
+
+/// 'add' keeps existing tags, 'remove' drops the listed tags, 'replace' makes
+/// the listed tags the note's only tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMode {
+    Add,
+    Remove,
+    Replace,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateNoteTagsArgs {
+    /// ID of the note to update
+    pub note_id: u64,
+    pub mode: TagMode,
+    /// Tags to add, remove or set. Tags cannot contain spaces
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateNoteTagsResponse {
+    pub note_id: u64,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+pub struct UpdateNoteTagsTool;
+
+impl UpdateNoteTagsTool {
+    async fn update_tags(&self, note_id: u64, mode: TagMode, tags: Vec<String>) -> Result<UpdateNoteTagsResponse> {
+        let before = fetch_note(note_id)?.tags;
+
+        let (to_add, to_remove): (Vec<String>, Vec<String>) = match mode {
+            TagMode::Add => (tags, Vec::new()),
+            TagMode::Remove => (Vec::new(), tags),
+            TagMode::Replace => (
+                tags.iter().filter(|t| !before.contains(t)).cloned().collect(),
+                before.iter().filter(|t| !tags.contains(t)).cloned().collect(),
+            ),
+        };
+
+        if !to_remove.is_empty() {
+            anki_connect::remove_tags(&[note_id], &to_remove)
+                .map_err(|e| anyhow!("Failed to remove tags from note {}: {}", note_id, e))?;
+        }
+
+        if !to_add.is_empty() {
+            anki_connect::add_tags(&[note_id], &to_add)
+                .map_err(|e| anyhow!("Failed to add tags to note {}: {}", note_id, e))?;
+        }
+
+        let after = fetch_note(note_id)?.tags;
+
+        tracing::info!("Updated tags of note {}", note_id);
+
+        Ok(UpdateNoteTagsResponse {
+            note_id,
+            added: after.iter().filter(|t| !before.contains(t)).cloned().collect(),
+            removed: before.iter().filter(|t| !after.contains(t)).cloned().collect(),
+            before,
+            after,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for UpdateNoteTagsTool {
+    type Args = UpdateNoteTagsArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Adds, removes or replaces the tags of an existing note. Returns the tags before and after the change".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::non_destructive()),
+        }
+    }
+
+    async fn call(&self, args: UpdateNoteTagsArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let errors: Vec<FieldError> = args.tags
+            .iter()
+            .enumerate()
+            .filter(|(_, tag)| tag.is_empty() || tag.chars().any(char::is_whitespace))
+            .map(|(i, tag)| FieldError {
+                field: format!("tags/{}", i),
+                message: format!("invalid tag '{}': tags must be non-empty and cannot contain whitespace", tag),
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(InvalidArguments { errors }.into());
         }
+
+        json_result(self.update_tags(args.note_id, args.mode, args.tags).await, "updating note tags")
     }
-}
\ No newline at end of file
+}
+
+const DELETE_PREVIEW_TTL_SECS: u64 = 600;
+const DELETE_PREVIEW_MAX_NOTES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeletePreviewNote {
+    pub note_id: u64,
+    pub model: String,
+    pub decks: Vec<String>,
+    pub fields: Vec<NoteField>,
+    pub tags: Vec<String>,
+    pub card_count: usize,
+    pub review_count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteNotesPreview {
+    pub preview_token: String,
+    pub expires_in_secs: u64,
+    pub total_notes: usize,
+    pub total_cards: usize,
+    pub total_reviews: u32,
+    /// Details of the first notes to be deleted; `total_notes` counts all of them
+    pub notes: Vec<DeletePreviewNote>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteNotesResult {
+    pub deleted_note_ids: Vec<u64>,
+    pub deleted_count: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteNotesArgs {
+    /// IDs of the notes to delete (use either this or 'query')
+    pub note_ids: Option<Vec<u64>>,
+    /// Anki search query selecting the notes to delete (use either this or 'note_ids')
+    pub query: Option<String>,
+    /// Actually delete the notes (default: false, which only previews)
+    #[serde(default)]
+    pub confirm: bool,
+    /// Token returned by the dry run; required when 'confirm' is true
+    pub preview_token: Option<String>,
+}
+
+struct PendingDeletion {
+    note_ids: Vec<u64>,
+    created_at: std::time::Instant,
+}
+
+/// Deletes notes in two steps: a dry run that returns a preview token, then a
+/// confirmed call that must pass the token back for the same set of notes.
+pub struct DeleteNotesTool {
+    pending: std::sync::Mutex<HashMap<String, PendingDeletion>>,
+}
+
+impl DeleteNotesTool {
+    pub fn new() -> Self {
+        Self {
+            pending: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn resolve_note_ids(&self, args: &DeleteNotesArgs) -> Result<Vec<u64>> {
+        let mut note_ids: Vec<u64> = match (&args.note_ids, &args.query) {
+            (Some(ids), None) => ids.clone(),
+            (None, Some(query)) => anki_connect::find_notes(query)
+                .map_err(|e| anyhow!("Failed to search notes: {}", e))?,
+            _ => return Err(InvalidArguments::field("", "exactly one of 'note_ids' or 'query' must be provided").into()),
+        };
+
+        note_ids.sort_unstable();
+        note_ids.dedup();
+        Ok(note_ids)
+    }
+
+    async fn preview(&self, note_ids: Vec<u64>) -> Result<DeleteNotesPreview> {
+        let notes = anki_connect::notes_info(&note_ids)
+            .map_err(|e| anyhow!("Failed to retrieve note details: {}", e))?;
+
+        // The confirmed call deletes the notes it is given, so every one of them
+        // must be in the preview
+        let missing: Vec<String> = note_ids
+            .iter()
+            .filter(|id| !notes.iter().any(|note| note.note_id == **id))
+            .map(u64::to_string)
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!("Notes not found: {}", missing.join(", ")));
+        }
+
+        if notes.is_empty() {
+            return Err(anyhow!("No matching notes found; nothing would be deleted"));
+        }
+
+        let card_ids: Vec<u64> = notes.iter().flat_map(|n| n.cards.iter().copied()).collect();
+        let cards = anki_connect::cards_info(&card_ids)
+            .map_err(|e| anyhow!("Failed to retrieve card details: {}", e))?;
+
+        let mut preview_notes: Vec<DeletePreviewNote> = notes
+            .iter()
+            .map(|note| {
+                let note_cards: Vec<&anki_connect::CardInfoDto> = cards.iter().filter(|c| c.note == note.note_id).collect();
+                let mut decks: Vec<String> = note_cards.iter().map(|c| c.deck_name.clone()).collect();
+                decks.sort();
+                decks.dedup();
+
+                let details = NoteDetails::from(note);
+                DeletePreviewNote {
+                    note_id: note.note_id,
+                    model: details.model,
+                    decks,
+                    fields: details.fields,
+                    tags: details.tags,
+                    card_count: note_cards.len(),
+                    review_count: note_cards.iter().map(|c| c.reps).sum(),
+                }
+            })
+            .collect();
+
+        let total_cards = preview_notes.iter().map(|n| n.card_count).sum();
+        let total_reviews = preview_notes.iter().map(|n| n.review_count).sum();
+        let existing_ids: Vec<u64> = preview_notes.iter().map(|n| n.note_id).collect();
+        let total_notes = existing_ids.len();
+        preview_notes.truncate(DELETE_PREVIEW_MAX_NOTES);
+
+        let token = uuid::Uuid::new_v4().to_string();
+        {
+            let mut pending = self.pending.lock().map_err(|_| anyhow!("Preview token store is poisoned"))?;
+            pending.retain(|_, p| p.created_at.elapsed().as_secs() < DELETE_PREVIEW_TTL_SECS);
+            pending.insert(token.clone(), PendingDeletion {
+                note_ids: existing_ids,
+                created_at: std::time::Instant::now(),
+            });
+        }
+
+        Ok(DeleteNotesPreview {
+            preview_token: token,
+            expires_in_secs: DELETE_PREVIEW_TTL_SECS,
+            total_notes,
+            total_cards,
+            total_reviews,
+            notes: preview_notes,
+        })
+    }
+
+    async fn run_preview(&self, args: &DeleteNotesArgs) -> Result<DeleteNotesPreview> {
+        self.preview(self.resolve_note_ids(args)?).await
+    }
+
+    async fn run_delete(&self, args: &DeleteNotesArgs, token: &str) -> Result<DeleteNotesResult> {
+        self.delete(anki_connect::client(), self.resolve_note_ids(args)?, token).await
+    }
+
+    async fn delete(&self, client: &AnkiClient, note_ids: Vec<u64>, token: &str) -> Result<DeleteNotesResult> {
+        // The lock is held until the notes are deleted so a token cannot be used twice
+        let mut pending = self.pending.lock()
+            .map_err(|_| anyhow!("Preview token store is poisoned"))?;
+
+        let preview = pending.get(token)
+            .ok_or_else(|| anyhow!("Unknown or already used preview token. Run delete_notes without 'confirm' to get a new one"))?;
+
+        if preview.created_at.elapsed().as_secs() >= DELETE_PREVIEW_TTL_SECS {
+            pending.remove(token);
+            return Err(anyhow!("Preview token has expired. Run delete_notes without 'confirm' to get a new one"));
+        }
+
+        // Exactly the notes shown in the preview must be deleted: a query that now
+        // matches more notes, or fewer because some were deleted in the meantime,
+        // needs a fresh preview. Deleting a subset of a preview is not allowed either
+        let mut previewed = preview.note_ids.clone();
+        previewed.sort_unstable();
+        if note_ids != previewed {
+            return Err(anyhow!("The notes to delete differ from the previewed notes. Run delete_notes without 'confirm' to get a new preview"));
+        }
+
+        let ids: Vec<NoteId> = note_ids.iter().copied().map(NoteId).collect();
+        client.cards().delete_notes(&ids)
+            .map_err(|e| anyhow!("Failed to delete notes: {}", e))?;
+
+        pending.remove(token);
+        tracing::info!("Deleted {} note(s)", ids.len());
+
+        Ok(DeleteNotesResult {
+            deleted_count: note_ids.len(),
+            deleted_note_ids: note_ids,
+        })
+    }
+}
+
+impl Default for DeleteNotesTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TypedTool for DeleteNotesTool {
+    type Args = DeleteNotesArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Deletes notes and all their cards. Without 'confirm' this is a dry run that lists what would be deleted and returns a preview_token; call again with exactly the same notes, confirm: true and that token to actually delete. Deletion cannot be undone".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: DeleteNotesArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        if args.confirm && args.preview_token.is_none() {
+            return Err(InvalidArguments::field("preview_token", "required when 'confirm' is true; run delete_notes without 'confirm' first").into());
+        }
+
+        match args.preview_token.as_deref().filter(|_| args.confirm) {
+            Some(token) => json_result(self.run_delete(&args, token).await, "deleting notes"),
+            None => json_result(self.run_preview(&args).await, "deleting notes"),
+        }
+    }
+}
+
+pub(crate) fn fetch_hierarchy(client: &AnkiClient) -> Result<Vec<DeckHierarchyNode>> {
+    let tree = client.decks().get_tree()
+        .map_err(|e| anyhow!("Failed to get deck hierarchy: {}", e))?;
+
+    Ok(tree.iter().map(DeckHierarchyNode::from).collect())
+}
+
+/// Trims the deck name passed as argument `field`, rejecting empty parts.
+fn validate_deck_name(field: &str, name: &str) -> Result<String> {
+    let name = name.trim();
+
+    if name.is_empty() || name.split("::").any(|part| part.trim().is_empty()) {
+        return Err(InvalidArguments::field(field, format!("invalid deck name '{}': every part between '::' must be non-empty", name)).into());
+    }
+
+    Ok(name.to_string())
+}
+
+/// Returns the deck and all of its subdecks, parents before children.
+pub(crate) fn deck_subtree<'a>(decks: &'a [Deck], name: &str) -> Vec<&'a Deck> {
+    let prefix = format!("{}::", name);
+    let mut subtree: Vec<&Deck> = decks
+        .iter()
+        .filter(|d| d.name() == name || d.name().starts_with(&prefix))
+        .collect();
+    subtree.sort_by(|a, b| a.name().cmp(b.name()));
+    subtree
+}
+
+fn is_in_subtree(name: &str, root: &str) -> bool {
+    name == root || name.starts_with(&format!("{}::", root))
+}
+
+/// Fails if any card still belongs to the deck tree, so deleting it cannot lose cards.
+fn ensure_deck_empty(deck: &str) -> Result<()> {
+    let remaining = anki_connect::find_cards(&anki_connect::deck_query(deck))
+        .map_err(|e| anyhow!("Failed to search cards in deck '{}': {}", deck, e))?;
+
+    if !remaining.is_empty() {
+        return Err(anyhow!(
+            "{} card(s) could not be moved out of deck '{}' (they may be in a filtered deck); the deck was left in place",
+            remaining.len(),
+            deck
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateDeckResponse {
+    pub deck: String,
+    pub deck_id: u64,
+    pub created: bool,
+    pub hierarchy: Vec<DeckHierarchyNode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreateDeckArgs {
+    /// Full deck name, e.g. "Japanese::Vocab::N5"
+    pub name: String,
+}
+
+pub struct CreateDeckTool;
+
+impl CreateDeckTool {
+    async fn create_deck(&self, name: &str) -> Result<CreateDeckResponse> {
+        let client = anki_connect::client();
+        let name = validate_deck_name("name", name)?;
+
+        let existed = client.decks().exists(&name)
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?;
+
+        // createDeck returns the existing deck's ID if it is already there
+        let deck_id = client.decks().create(&name)
+            .map_err(|e| anyhow!("Failed to create deck '{}': {}", name, e))?;
+
+        if !existed {
+            tracing::info!("Created deck '{}'", name);
+        }
+
+        Ok(CreateDeckResponse {
+            deck: name,
+            deck_id: deck_id.0,
+            created: !existed,
+            hierarchy: fetch_hierarchy(client)?,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for CreateDeckTool {
+    type Args = CreateDeckArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Creates a deck (use '::' for subdecks, missing parents are created too) and returns the resulting deck hierarchy".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::non_destructive()),
+        }
+    }
+
+    async fn call(&self, args: CreateDeckArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        json_result(self.create_deck(&args.name).await, "creating deck")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamedDeck {
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenameDeckResponse {
+    pub renamed: Vec<RenamedDeck>,
+    pub cards_moved: usize,
+    pub hierarchy: Vec<DeckHierarchyNode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RenameDeckArgs {
+    /// Full name of the deck to rename
+    pub deck: String,
+    /// New full deck name; must not exist yet
+    pub new_name: String,
+}
+
+pub struct RenameDeckTool;
+
+/// The decks and cards a rename will touch, worked out before changing anything.
+struct RenamePlan {
+    renamed: Vec<RenamedDeck>,
+    /// Options preset of each deck in `renamed`
+    config_ids: Vec<u64>,
+    /// Ancestors of the new name that don't exist yet, parents first
+    new_parents: Vec<String>,
+    moves: Vec<(Vec<u64>, RenamedDeck)>,
+}
+
+/// What a rename has changed so far, so a failure can be undone.
+#[derive(Default)]
+struct RenameProgress {
+    created: Vec<String>,
+    moved: Vec<(Vec<u64>, RenamedDeck)>,
+}
+
+impl RenameProgress {
+    fn describe_moved(&self) -> String {
+        if self.moved.is_empty() {
+            return "none".to_string();
+        }
+        self.moved.iter()
+            .map(|(ids, r)| format!("'{}' -> '{}' ({} cards)", r.old_name, r.new_name, ids.len()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl RenameDeckTool {
+    fn apply(
+        &self,
+        client: &AnkiClient,
+        deck: &str,
+        plan: &RenamePlan,
+        done: &mut RenameProgress,
+    ) -> Result<()> {
+        for (r, config_id) in plan.renamed.iter().zip(&plan.config_ids) {
+            client.decks().create(&r.new_name)
+                .map_err(|e| anyhow!("Failed to create deck '{}': {}", r.new_name, e))?;
+            if done.created.is_empty() {
+                // Creating the first deck also created any missing parents
+                done.created.extend(plan.new_parents.iter().cloned());
+            }
+            done.created.push(r.new_name.clone());
+
+            anki_connect::set_deck_config_id(&[&r.new_name], *config_id)
+                .map_err(|e| anyhow!("Failed to copy options to deck '{}': {}", r.new_name, e))?;
+        }
+
+        for (ids, r) in &plan.moves {
+            if ids.is_empty() {
+                continue;
+            }
+            anki_connect::change_deck(ids, &r.new_name)
+                .map_err(|e| anyhow!("Failed to move cards to '{}': {}", r.new_name, e))?;
+            done.moved.push((ids.clone(), (*r).clone()));
+        }
+
+        ensure_deck_empty(deck)?;
+        client.decks().delete(deck, true)
+            .map_err(|e| anyhow!("Failed to remove old deck '{}': {}", deck, e))?;
+
+        Ok(())
+    }
+
+    /// Moves cards back and removes the decks created by a failed rename. New
+    /// decks that still hold cards are left alone rather than deleted with them.
+    fn roll_back(&self, client: &AnkiClient, done: &RenameProgress) -> Result<()> {
+        for (ids, r) in &done.moved {
+            anki_connect::change_deck(ids, &r.old_name)
+                .map_err(|e| anyhow!("Failed to move cards back to '{}': {}", r.old_name, e))?;
+        }
+
+        // Children first, so removing a parent never takes a child with it
+        for name in done.created.iter().rev() {
+            ensure_deck_empty(name)?;
+            client.decks().delete(name, true)
+                .map_err(|e| anyhow!("Failed to remove new deck '{}': {}", name, e))?;
+        }
+
+        Ok(())
+    }
+
+    async fn rename_deck(&self, deck: &str, new_name: &str) -> Result<RenameDeckResponse> {
+        let client = anki_connect::client();
+        let new_name = validate_deck_name("new_name", new_name)?;
+
+        let decks = client.decks().get_all()
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?;
+
+        if !decks.iter().any(|d| d.name() == deck) {
+            return Err(anyhow!("Deck '{}' not found", deck));
+        }
+        if decks.iter().any(|d| d.name() == new_name) {
+            return Err(anyhow!("A deck named '{}' already exists", new_name));
+        }
+        if is_in_subtree(&new_name, deck) {
+            return Err(anyhow!("Cannot move deck '{}' underneath itself", deck));
+        }
+
+        // AnkiConnect has no rename action: recreate the subtree under the new
+        // name with the same options presets, move every card across, then
+        // remove the emptied old decks
+        let mut renamed = Vec::new();
+        let mut config_ids = Vec::new();
+        for d in deck_subtree(&decks, deck) {
+            let config_id = anki_connect::deck_config_id(d.name())
+                .map_err(|e| anyhow!("Failed to read options of deck '{}': {}", d.name(), e))?
+                .ok_or_else(|| anyhow!("Deck '{}' is a filtered deck, which cannot be recreated through AnkiConnect; rename it in Anki instead", d.name()))?;
+            config_ids.push(config_id);
+            renamed.push(RenamedDeck {
+                old_name: d.name().to_string(),
+                new_name: format!("{}{}", new_name, &d.name()[deck.len()..]),
+            });
+        }
+
+        let card_ids = anki_connect::find_cards(&anki_connect::deck_query(deck))
+            .map_err(|e| anyhow!("Failed to search cards in deck '{}': {}", deck, e))?;
+        let cards = anki_connect::cards_info(&card_ids)
+            .map_err(|e| anyhow!("Failed to retrieve card details: {}", e))?;
+
+        // Cards borrowed by a filtered deck elsewhere still count as part of this
+        // deck, but moving them would take them out of the filtered deck
+        let borrowed = cards.iter()
+            .filter(|c| !renamed.iter().any(|r| r.old_name == c.deck_name))
+            .count();
+        if borrowed > 0 {
+            return Err(anyhow!(
+                "{} card(s) of deck '{}' are currently in a filtered deck; empty or rebuild the filtered deck in Anki first. Nothing was changed",
+                borrowed,
+                deck
+            ));
+        }
+
+        let moves = renamed.iter()
+            .map(|r| {
+                let ids = cards.iter()
+                    .filter(|c| c.deck_name == r.old_name)
+                    .map(|c| c.card_id)
+                    .collect();
+                (ids, r.clone())
+            })
+            .collect();
+
+        let new_parents = new_name.match_indices("::")
+            .map(|(i, _)| &new_name[..i])
+            .filter(|parent| !decks.iter().any(|d| d.name() == *parent))
+            .map(str::to_string)
+            .collect();
+
+        let plan = RenamePlan { renamed, config_ids, new_parents, moves };
+        let mut done = RenameProgress::default();
+        if let Err(e) = self.apply(client, deck, &plan, &mut done) {
+            return Err(match self.roll_back(client, &done) {
+                Ok(()) => anyhow!("{}. The rename was rolled back and deck '{}' is unchanged", e, deck),
+                Err(rollback) => anyhow!(
+                    "{}. Rolling the rename back also failed: {}. Cards of these decks are now in their new deck: {}",
+                    e,
+                    rollback,
+                    done.describe_moved()
+                ),
+            });
+        }
+
+        let cards_moved = done.moved.iter().map(|(ids, _)| ids.len()).sum();
+
+        tracing::info!("Renamed deck '{}' to '{}' ({} cards moved)", deck, new_name, cards_moved);
+
+        Ok(RenameDeckResponse {
+            renamed: plan.renamed,
+            cards_moved,
+            hierarchy: fetch_hierarchy(client)?,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for RenameDeckTool {
+    type Args = RenameDeckArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Renames a deck together with its subdecks and cards. Use a '::' path to move it under another deck, e.g. renaming \"Verbs\" to \"Japanese::Verbs\". The decks are recreated under the new name: options presets are kept, but deck IDs and descriptions are not. If a step fails, the changes made so far are rolled back. Returns the resulting deck hierarchy".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::non_destructive()),
+        }
+    }
+
+    async fn call(&self, args: RenameDeckArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        json_result(self.rename_deck(&args.deck, &args.new_name).await, "renaming deck")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteDeckResponse {
+    pub deleted_decks: Vec<String>,
+    pub cards_deleted: usize,
+    pub cards_moved: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moved_to: Option<String>,
+    pub hierarchy: Vec<DeckHierarchyNode>,
+}
+
+/// What happens to the cards of a deleted deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletedCards {
+    /// Delete the cards with the deck (irreversible)
+    Delete,
+    /// Move the cards to 'move_to' before deleting the deck
+    Move,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteDeckArgs {
+    /// Full name of the deck to delete
+    pub deck: String,
+    pub cards: DeletedCards,
+    /// Deck that receives the cards when 'cards' is 'move'; created if missing
+    pub move_to: Option<String>,
+}
+
+pub struct DeleteDeckTool;
+
+impl DeleteDeckTool {
+    async fn delete_deck(&self, deck: &str, move_to: Option<&str>) -> Result<DeleteDeckResponse> {
+        let client = anki_connect::client();
+
+        let decks = client.decks().get_all()
+            .map_err(|e| anyhow!("Failed to retrieve decks from Anki: {}", e))?;
+
+        let deleted_decks: Vec<String> = deck_subtree(&decks, deck)
+            .into_iter()
+            .map(|d| d.name().to_string())
+            .collect();
+
+        if deleted_decks.is_empty() {
+            return Err(anyhow!("Deck '{}' not found", deck));
+        }
+
+        let card_ids = anki_connect::find_cards(&anki_connect::deck_query(deck))
+            .map_err(|e| anyhow!("Failed to search cards in deck '{}': {}", deck, e))?;
+
+        let (cards_deleted, cards_moved) = match move_to {
+            Some(target) => {
+                let target = validate_deck_name("move_to", target)?;
+                if is_in_subtree(&target, deck) {
+                    return Err(anyhow!("Cannot move cards to '{}' because it is deleted along with '{}'", target, deck));
+                }
+
+                if !card_ids.is_empty() {
+                    anki_connect::change_deck(&card_ids, &target)
+                        .map_err(|e| anyhow!("Failed to move cards to '{}': {}", target, e))?;
+                }
+                ensure_deck_empty(deck)?;
+                (0, card_ids.len())
+            }
+            None => (card_ids.len(), 0),
+        };
+
+        client.decks().delete(deck, true)
+            .map_err(|e| anyhow!("Failed to delete deck '{}': {}", deck, e))?;
+
+        tracing::info!("Deleted deck '{}' ({} cards deleted, {} moved)", deck, cards_deleted, cards_moved);
+
+        Ok(DeleteDeckResponse {
+            deleted_decks,
+            cards_deleted,
+            cards_moved,
+            moved_to: move_to.map(|s| s.trim().to_string()),
+            hierarchy: fetch_hierarchy(client)?,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for DeleteDeckTool {
+    type Args = DeleteDeckArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Deletes a deck and its subdecks. 'cards' chooses whether their cards are deleted or moved to another deck first. Returns the resulting deck hierarchy".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: DeleteDeckArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let move_to = match args.cards {
+            DeletedCards::Delete => None,
+            DeletedCards::Move => Some(
+                args.move_to
+                    .as_deref()
+                    .ok_or_else(|| InvalidArguments::field("move_to", "required when 'cards' is 'move'"))?,
+            ),
+        };
+
+        json_result(self.delete_deck(&args.deck, move_to).await, "deleting deck")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteTypeField {
+    pub name: String,
+    pub ord: usize,
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardTemplateInfo {
+    pub name: String,
+    pub ord: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub front: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub back: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteTypeInfo {
+    pub id: String,
+    pub name: String,
+    pub is_cloze: bool,
+    pub sort_field: String,
+    pub fields: Vec<NoteTypeField>,
+    pub templates: Vec<CardTemplateInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub css: Option<String>,
+}
+
+impl NoteTypeInfo {
+    fn from_model(model: &anki_connect::ModelDto, include_templates: bool) -> Self {
+        let mut fields: Vec<NoteTypeField> = model.flds
+            .iter()
+            .map(|f| NoteTypeField {
+                name: f.name.clone(),
+                ord: f.ord,
+                description: f.description.clone(),
+            })
+            .collect();
+        fields.sort_by_key(|f| f.ord);
+
+        let mut templates: Vec<CardTemplateInfo> = model.tmpls
+            .iter()
+            .map(|t| CardTemplateInfo {
+                name: t.name.clone(),
+                ord: t.ord,
+                front: include_templates.then(|| t.qfmt.clone()),
+                back: include_templates.then(|| t.afmt.clone()),
+            })
+            .collect();
+        templates.sort_by_key(|t| t.ord);
+
+        NoteTypeInfo {
+            id: model.id.to_string(),
+            name: model.name.clone(),
+            is_cloze: model.kind == 1,
+            sort_field: fields.iter().find(|f| f.ord == model.sortf).map(|f| f.name.clone()).unwrap_or_default(),
+            fields,
+            templates,
+            css: include_templates.then(|| model.css.clone()),
+        }
+    }
+}
+
+fn fetch_note_type(name: &str) -> Result<anki_connect::ModelDto> {
+    anki_connect::find_models_by_name(&[name.to_string()])
+        .map_err(|e| anyhow!("Failed to retrieve note type '{}': {}", name, e))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Note type '{}' not found", name))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListNoteTypesResponse {
+    pub note_types: Vec<NoteTypeInfo>,
+    pub total: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListNoteTypesArgs {
+    /// Also return template HTML and CSS for every note type (default: false)
+    #[serde(default)]
+    pub include_templates: bool,
+}
+
+pub struct ListNoteTypesTool;
+
+impl ListNoteTypesTool {
+    pub(crate) async fn list_note_types(&self, include_templates: bool) -> Result<ListNoteTypesResponse> {
+        let mut names: Vec<String> = anki_connect::model_names_and_ids()
+            .map_err(|e| anyhow!("Failed to retrieve note types from Anki: {}", e))?
+            .into_keys()
+            .collect();
+        names.sort();
+
+        let models = anki_connect::find_models_by_name(&names)
+            .map_err(|e| anyhow!("Failed to retrieve note type details: {}", e))?;
+
+        let note_types: Vec<NoteTypeInfo> = models
+            .iter()
+            .map(|m| NoteTypeInfo::from_model(m, include_templates))
+            .collect();
+
+        Ok(ListNoteTypesResponse {
+            total: note_types.len(),
+            note_types,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for ListNoteTypesTool {
+    type Args = ListNoteTypesArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Lists all note types (models) with their fields in order, card template names and whether they are cloze types. Use this to find valid field names before adding notes".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
+        }
+    }
+
+    async fn call(&self, args: ListNoteTypesArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        json_result(self.list_note_types(args.include_templates).await, "listing note types")
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetNoteTypeArgs {
+    /// Name of the note type, e.g. "Basic"
+    pub name: String,
+}
+
+pub struct GetNoteTypeTool;
+
+impl GetNoteTypeTool {
+    async fn get_note_type(&self, name: &str) -> Result<NoteTypeInfo> {
+        Ok(NoteTypeInfo::from_model(&fetch_note_type(name)?, true))
+    }
+}
+
+#[async_trait]
+impl TypedTool for GetNoteTypeTool {
+    type Args = GetNoteTypeArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Returns the full definition of a note type (model): fields in order, card templates (front/back HTML), CSS and whether it is a cloze type".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
+        }
+    }
+
+    async fn call(&self, args: GetNoteTypeArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        json_result(self.get_note_type(&args.name).await, "getting note type")
+    }
+}
+
+const DEFAULT_NOTE_TYPE_CSS: &str = ".card {\n    font-family: arial;\n    font-size: 20px;\n    text-align: center;\n    color: black;\n    background-color: white;\n}\n";
+
+/// A card template of a new note type.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NewTemplate {
+    pub name: String,
+    /// Front (question) HTML, e.g. "{{Front}}"
+    pub front: String,
+    /// Back (answer) HTML, e.g. "{{FrontSide}}<hr id=answer>{{Back}}"
+    pub back: String,
+}
+
+/// A card template to update, or to add if no template has its name.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateSpec {
+    /// Template name, e.g. "Card 1"
+    pub name: String,
+    /// New front HTML; omit to keep the current one
+    #[serde(default)]
+    pub front: Option<String>,
+    /// New back HTML; omit to keep the current one
+    #[serde(default)]
+    pub back: Option<String>,
+}
+
+fn note_type_response(name: &str) -> Result<NoteTypeInfo> {
+    Ok(NoteTypeInfo::from_model(&fetch_note_type(name)?, true))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreateNoteTypeArgs {
+    /// Name of the new note type
+    pub name: String,
+    /// Field names in order; the first one is used for duplicate checks
+    pub fields: Vec<String>,
+    /// Card templates; each produces one card per note
+    #[schemars(length(min = 1))]
+    pub templates: Vec<NewTemplate>,
+    /// Styling shared by all card templates (default: Anki's standard styling)
+    pub css: Option<String>,
+    /// Create a cloze note type (default: false)
+    #[serde(default)]
+    pub is_cloze: bool,
+}
+
+pub struct CreateNoteTypeTool;
+
+impl CreateNoteTypeTool {
+    async fn create_note_type(
+        &self,
+        name: &str,
+        fields: Vec<String>,
+        templates: Vec<NewTemplate>,
+        css: Option<String>,
+        is_cloze: bool,
+    ) -> Result<NoteTypeInfo> {
+        if name.trim().is_empty() {
+            return Err(anyhow!("Note type name cannot be empty"));
+        }
+
+        if fields.is_empty() || fields.iter().any(|f| f.trim().is_empty()) {
+            return Err(anyhow!("'fields' must contain at least one non-empty field name"));
+        }
+
+        if let Some(duplicate) = fields.iter().enumerate().find(|(i, f)| fields[..*i].contains(f)).map(|(_, f)| f) {
+            return Err(anyhow!("Field '{}' is listed more than once", duplicate));
+        }
+
+        let existing = anki_connect::model_names_and_ids()
+            .map_err(|e| anyhow!("Failed to retrieve note types from Anki: {}", e))?;
+        if existing.contains_key(name) {
+            return Err(anyhow!("A note type named '{}' already exists", name));
+        }
+
+        let templates: Vec<(String, String, String)> = templates
+            .into_iter()
+            .map(|t| (t.name, t.front, t.back))
+            .collect();
+
+        let css = css.unwrap_or_else(|| DEFAULT_NOTE_TYPE_CSS.to_string());
+
+        anki_connect::create_model(name, &fields, &css, is_cloze, &templates)
+            .map_err(|e| anyhow!("Failed to create note type '{}': {}", name, e))?;
+
+        tracing::info!("Created note type '{}'", name);
+
+        note_type_response(name)
+    }
+}
+
+#[async_trait]
+impl TypedTool for CreateNoteTypeTool {
+    type Args = CreateNoteTypeArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Creates a new note type (model) from fields, card templates and CSS. Returns the resulting note type definition".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::non_destructive()),
+        }
+    }
+
+    async fn call(&self, args: CreateNoteTypeArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let result = self.create_note_type(&args.name, args.fields, args.templates, args.css, args.is_cloze).await;
+        json_result(result, "creating note type")
+    }
+}
+
+/// A change to a note type's fields. Indexes are 0-based
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FieldOperation {
+    Add {
+        /// Field to add
+        name: String,
+        /// Position of the new field (default: the end)
+        #[serde(default)]
+        index: Option<usize>,
+    },
+    Rename {
+        /// Field to rename
+        name: String,
+        new_name: String,
+    },
+    Reposition {
+        /// Field to move
+        name: String,
+        /// Target position
+        index: usize,
+    },
+    Remove {
+        /// Field to remove
+        name: String,
+    },
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EditNoteTypeFieldsArgs {
+    /// Name of the note type to edit
+    pub note_type: String,
+    /// Field operations, applied in order
+    pub operations: Vec<FieldOperation>,
+}
+
+pub struct EditNoteTypeFieldsTool;
+
+impl EditNoteTypeFieldsTool {
+    /// Replays the operations on a copy of the field list so that an invalid
+    /// operation is reported before anything in the collection is changed.
+    fn check_operations(&self, fields: &[String], operations: &[FieldOperation]) -> Result<()> {
+        let mut fields = fields.to_vec();
+        let position = |fields: &[String], name: &str| {
+            fields.iter().position(|f| f == name).ok_or_else(|| anyhow!("Field '{}' does not exist", name))
+        };
+
+        for (i, operation) in operations.iter().enumerate() {
+            let step = |e: anyhow::Error| anyhow!("Operation {}: {}", i, e);
+            match operation {
+                FieldOperation::Add { name, index } => {
+                    if name.trim().is_empty() {
+                        return Err(step(anyhow!("Field name cannot be empty")));
+                    }
+                    if fields.contains(name) {
+                        return Err(step(anyhow!("Field '{}' already exists", name)));
+                    }
+                    let index = index.unwrap_or(fields.len());
+                    if index > fields.len() {
+                        return Err(step(anyhow!("Index {} is out of range (0 to {})", index, fields.len())));
+                    }
+                    fields.insert(index, name.clone());
+                }
+                FieldOperation::Rename { name, new_name } => {
+                    let pos = position(&fields, name).map_err(step)?;
+                    if new_name.trim().is_empty() || fields.contains(new_name) {
+                        return Err(step(anyhow!("Cannot rename '{}' to '{}': name is empty or already used", name, new_name)));
+                    }
+                    fields[pos] = new_name.clone();
+                }
+                FieldOperation::Reposition { name, index } => {
+                    let pos = position(&fields, name).map_err(step)?;
+                    if *index >= fields.len() {
+                        return Err(step(anyhow!("Index {} is out of range (0 to {})", index, fields.len() - 1)));
+                    }
+                    let field = fields.remove(pos);
+                    fields.insert(*index, field);
+                }
+                FieldOperation::Remove { name } => {
+                    let pos = position(&fields, name).map_err(step)?;
+                    if fields.len() == 1 {
+                        return Err(step(anyhow!("Cannot remove '{}': a note type needs at least one field", name)));
+                    }
+                    fields.remove(pos);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn edit_fields(&self, note_type: &str, operations: Vec<FieldOperation>) -> Result<NoteTypeInfo> {
+        let current: Vec<String> = NoteTypeInfo::from_model(&fetch_note_type(note_type)?, false)
+            .fields
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        self.check_operations(&current, &operations)?;
+
+        let mut field_count = current.len();
+        for operation in &operations {
+            let result = match operation {
+                FieldOperation::Add { name, index } => {
+                    field_count += 1;
+                    anki_connect::model_field_add(note_type, name, index.unwrap_or(field_count - 1))
+                }
+                FieldOperation::Rename { name, new_name } => anki_connect::model_field_rename(note_type, name, new_name),
+                FieldOperation::Reposition { name, index } => anki_connect::model_field_reposition(note_type, name, *index),
+                FieldOperation::Remove { name } => {
+                    field_count -= 1;
+                    anki_connect::model_field_remove(note_type, name)
+                }
+            };
+            result.map_err(|e| anyhow!("Failed to apply {:?}: {}", operation, e))?;
+        }
+
+        tracing::info!("Applied {} field operation(s) to note type '{}'", operations.len(), note_type);
+
+        note_type_response(note_type)
+    }
+}
+
+#[async_trait]
+impl TypedTool for EditNoteTypeFieldsTool {
+    type Args = EditNoteTypeFieldsArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Adds, renames, reorders or removes fields of a note type. Operations are applied in order after all of them have been validated. Removing a field deletes its content from every note of that type. Returns the resulting note type definition".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: EditNoteTypeFieldsArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let result = self.edit_fields(&args.note_type, args.operations).await;
+        json_result(result, "editing note type")
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateNoteTypeTemplatesArgs {
+    /// Name of the note type to update
+    pub note_type: String,
+    #[schemars(length(min = 1))]
+    pub templates: Vec<TemplateSpec>,
+}
+
+pub struct UpdateNoteTypeTemplatesTool;
+
+impl UpdateNoteTypeTemplatesTool {
+    async fn update_templates(&self, note_type: &str, templates: Vec<TemplateSpec>) -> Result<NoteTypeInfo> {
+        let model = fetch_note_type(note_type)?;
+
+        let mut updated = Vec::new();
+        let mut added = Vec::new();
+        for spec in templates {
+            match model.tmpls.iter().find(|t| t.name == spec.name) {
+                // Sides that are not given keep their current HTML
+                Some(current) => updated.push((
+                    spec.name,
+                    spec.front.unwrap_or_else(|| current.qfmt.clone()),
+                    spec.back.unwrap_or_else(|| current.afmt.clone()),
+                )),
+                None => match (spec.front, spec.back) {
+                    (Some(front), Some(back)) => added.push((spec.name, front, back)),
+                    _ => return Err(anyhow!("New template '{}' needs both 'front' and 'back'", spec.name)),
+                },
+            }
+        }
+
+        if !updated.is_empty() {
+            anki_connect::update_model_templates(note_type, &updated)
+                .map_err(|e| anyhow!("Failed to update templates: {}", e))?;
+        }
+
+        for (name, front, back) in &added {
+            anki_connect::model_template_add(note_type, name, front, back)
+                .map_err(|e| anyhow!("Failed to add template '{}': {}", name, e))?;
+        }
+
+        tracing::info!("Updated {} and added {} template(s) of note type '{}'", updated.len(), added.len(), note_type);
+
+        note_type_response(note_type)
+    }
+}
+
+#[async_trait]
+impl TypedTool for UpdateNoteTypeTemplatesTool {
+    type Args = UpdateNoteTypeTemplatesArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Updates the front/back HTML of a note type's card templates; templates with a new name are added. Returns the resulting note type definition".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: UpdateNoteTypeTemplatesArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let result = self.update_templates(&args.note_type, args.templates).await;
+        json_result(result, "updating note type")
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateNoteTypeStylingArgs {
+    /// Name of the note type to update
+    pub note_type: String,
+    /// New CSS shared by all card templates
+    pub css: String,
+}
+
+pub struct UpdateNoteTypeStylingTool;
+
+impl UpdateNoteTypeStylingTool {
+    async fn update_styling(&self, note_type: &str, css: &str) -> Result<NoteTypeInfo> {
+        fetch_note_type(note_type)?;
+
+        anki_connect::update_model_styling(note_type, css)
+            .map_err(|e| anyhow!("Failed to update styling: {}", e))?;
+
+        tracing::info!("Updated styling of note type '{}'", note_type);
+
+        note_type_response(note_type)
+    }
+}
+
+#[async_trait]
+impl TypedTool for UpdateNoteTypeStylingTool {
+    type Args = UpdateNoteTypeStylingArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Replaces the CSS styling of a note type. Returns the resulting note type definition".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: UpdateNoteTypeStylingArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let result = self.update_styling(&args.note_type, &args.css).await;
+        json_result(result, "updating note type")
+    }
+}
+
+const GET_CARDS_MAX: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardDetails {
+    pub card_id: u64,
+    pub note_id: u64,
+    pub deck: String,
+    pub model: String,
+    pub template: String,
+    pub ord: usize,
+    /// "new", "learning", "review" or "relearning"
+    pub card_type: String,
+    pub queue: i32,
+    pub suspended: bool,
+    pub buried: bool,
+    pub interval_days: i64,
+    pub ease_factor: f64,
+    pub due: i64,
+    /// Days until the card is due (negative when overdue); set for review and learning cards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_in_days: Option<i64>,
+    /// Unix timestamp for cards in intraday learning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<u64>,
+    /// Position in the new card queue for new cards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_position: Option<i64>,
+    pub reps: u32,
+    pub lapses: u32,
+    pub modified: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetCardsResponse {
+    pub total: usize,
+    pub truncated: bool,
+    pub cards: Vec<CardDetails>,
+}
+
+/// Today's day number as last worked out by [`today_day_number`].
+static TODAY_DAY_NUMBER: std::sync::Mutex<Option<i64>> = std::sync::Mutex::new(None);
+
+/// Anki stores review due dates as day numbers counted from the collection's
+/// creation, which AnkiConnect does not expose. Recover today's day number from
+/// one review card by binary searching its relative due date with `prop:due>=N`.
+/// The result is remembered, so later calls only confirm it with one `prop:due=N`
+/// search and repeat the binary search after the day rolls over.
+fn today_day_number(card_id: u64, due: i64) -> Result<i64> {
+    let cached = *TODAY_DAY_NUMBER.lock().map_err(|_| anyhow!("Day number cache is poisoned"))?;
+    if let Some(today) = cached {
+        let matches = anki_connect::find_cards(&format!("cid:{} prop:due={}", card_id, due - today))
+            .map_err(|e| anyhow!("Failed to determine due dates: {}", e))?;
+        if !matches.is_empty() {
+            return Ok(today);
+        }
+    }
+
+    let today = search_today_day_number(card_id, due)?;
+    *TODAY_DAY_NUMBER.lock().map_err(|_| anyhow!("Day number cache is poisoned"))? = Some(today);
+    Ok(today)
+}
+
+fn search_today_day_number(card_id: u64, due: i64) -> Result<i64> {
+    let (mut low, mut high) = (-100_000_i64, 100_000_i64);
+
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        let matches = anki_connect::find_cards(&format!("cid:{} prop:due>={}", card_id, mid))
+            .map_err(|e| anyhow!("Failed to determine due dates: {}", e))?;
+
+        if matches.is_empty() {
+            high = mid - 1;
+        } else {
+            low = mid;
+        }
+    }
+
+    Ok(due - low)
+}
+
+fn template_names(cards: &[anki_connect::CardInfoDto]) -> Result<HashMap<(String, usize), String>> {
+    let mut model_names: Vec<String> = cards.iter().map(|c| c.model_name.clone()).collect();
+    model_names.sort();
+    model_names.dedup();
+
+    let models = anki_connect::find_models_by_name(&model_names)
+        .map_err(|e| anyhow!("Failed to retrieve note types: {}", e))?;
+
+    let mut names = HashMap::new();
+    for card in cards {
+        let Some(model) = models.iter().find(|m| m.name == card.model_name) else {
+            continue;
+        };
+
+        // Cloze note types have a single template that produces one card per cloze number
+        let name = if model.kind == 1 {
+            model.tmpls.first().map(|t| format!("{} {}", t.name, card.ord + 1))
+        } else {
+            model.tmpls.iter().find(|t| t.ord == card.ord).map(|t| t.name.clone())
+        };
+
+        if let Some(name) = name {
+            names.insert((card.model_name.clone(), card.ord), name);
+        }
+    }
+
+    Ok(names)
+}
+
+/// Resolves the card selection shared by card tools: either `card_ids` or a search `query`.
+fn resolve_card_ids(card_ids: Option<&[u64]>, query: Option<&str>) -> Result<Vec<u64>> {
+    let mut card_ids: Vec<u64> = match (card_ids, query) {
+        (Some(ids), None) => ids.to_vec(),
+        (None, Some(query)) => anki_connect::find_cards(query)
+            .map_err(|e| anyhow!("Failed to search cards: {}", e))?,
+        _ => return Err(InvalidArguments::field("", "exactly one of 'card_ids' or 'query' must be provided").into()),
+    };
+
+    card_ids.sort_unstable();
+    card_ids.dedup();
+    Ok(card_ids)
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetCardsArgs {
+    /// IDs of the cards to inspect (use either this or 'query')
+    pub card_ids: Option<Vec<u64>>,
+    /// Anki search query selecting the cards, e.g. "deck:Japanese prop:lapses>3" (use either this or 'card_ids')
+    pub query: Option<String>,
+}
+
+pub struct GetCardsTool;
+
+impl GetCardsTool {
+    async fn get_cards(&self, args: &GetCardsArgs) -> Result<GetCardsResponse> {
+        let mut card_ids = resolve_card_ids(args.card_ids.as_deref(), args.query.as_deref())?;
+
+        let total = card_ids.len();
+        let truncated = total > GET_CARDS_MAX;
+        card_ids.truncate(GET_CARDS_MAX);
+
+        let cards = anki_connect::cards_info(&card_ids)
+            .map_err(|e| anyhow!("Failed to retrieve card details: {}", e))?;
+
+        let templates = template_names(&cards)?;
+
+        let review_card = cards.iter().find(|c| matches!(c.queue, 2 | 3));
+        let today = match review_card {
+            Some(card) => {
+                // Cards in filtered decks report their filtered position as `due`
+                let regular = anki_connect::find_cards(&format!("cid:{} -deck:filtered", card.card_id))
+                    .map_err(|e| anyhow!("Failed to search cards: {}", e))?;
+                if regular.is_empty() { None } else { Some(today_day_number(card.card_id, card.due)?) }
+            }
+            None => None,
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let cards = cards
+            .iter()
+            .map(|card| {
+                let (due_in_days, due_at, new_position) = match card.queue {
+                    0 => (None, None, Some(card.due)),
+                    1 | 4 => (Some((card.due - now).max(0) / 86_400), Some(card.due.max(0) as u64), None),
+                    2 | 3 => (today.map(|t| card.due - t), None, None),
+                    _ => (None, None, None),
+                };
+
+                CardDetails {
+                    card_id: card.card_id,
+                    note_id: card.note,
+                    deck: card.deck_name.clone(),
+                    model: card.model_name.clone(),
+                    template: templates.get(&(card.model_name.clone(), card.ord)).cloned().unwrap_or_default(),
+                    ord: card.ord,
+                    card_type: match card.card_type {
+                        0 => "new",
+                        1 => "learning",
+                        2 => "review",
+                        3 => "relearning",
+                        _ => "unknown",
+                    }.to_string(),
+                    queue: card.queue,
+                    suspended: card.queue == -1,
+                    buried: matches!(card.queue, -3 | -2),
+                    interval_days: card.interval.max(0),
+                    ease_factor: card.factor as f64 / 1000.0,
+                    due: card.due,
+                    due_in_days,
+                    due_at,
+                    new_position,
+                    reps: card.reps,
+                    lapses: card.lapses,
+                    modified: card.modified,
+                }
+            })
+            .collect();
+
+        Ok(GetCardsResponse {
+            total,
+            truncated,
+            cards,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for GetCardsTool {
+    type Args = GetCardsArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some(format!("Returns scheduling data for cards: deck, template, interval, ease, due, reps, lapses, queue/type and suspension state. Select cards by ID or Anki search query (at most {} cards)", GET_CARDS_MAX)),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
+        }
+    }
+
+    async fn call(&self, args: GetCardsArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        json_result(self.get_cards(&args).await, "getting cards")
+    }
+}
+
+pub const DEFAULT_MAX_CARDS_PER_CHANGE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleAction {
+    Suspend,
+    Unsuspend,
+    Bury,
+    Forget,
+    SetDueDate,
+}
+
+impl ScheduleAction {
+    fn name(&self) -> &'static str {
+        match self {
+            ScheduleAction::Suspend => "suspend",
+            ScheduleAction::Unsuspend => "unsuspend",
+            ScheduleAction::Bury => "bury",
+            ScheduleAction::Forget => "forget",
+            ScheduleAction::SetDueDate => "set_due_date",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ScheduleAction::Suspend => "Suspends cards so they are not shown in reviews until unsuspended",
+            ScheduleAction::Unsuspend => "Unsuspends cards so they are shown in reviews again",
+            ScheduleAction::Bury => "Buries cards so they are hidden until the next day",
+            ScheduleAction::Forget => "Resets cards to new, discarding their scheduling (review history is kept)",
+            ScheduleAction::SetDueDate => "Sets the due date of cards, turning new cards into review cards, and optionally their interval",
+        }
+    }
+
+    /// Forgetting and setting due dates overwrite scheduling; the others are
+    /// undone by their counterpart action.
+    fn annotations(&self) -> ToolAnnotations {
+        match self {
+            ScheduleAction::Forget | ScheduleAction::SetDueDate => ToolAnnotations::destructive(),
+            _ => ToolAnnotations {
+                idempotent_hint: Some(true),
+                ..ToolAnnotations::non_destructive()
+            },
+        }
+    }
+
+    /// Whether applying the action would change the card; cards that are already
+    /// in the target state are skipped and reported as unchanged.
+    fn changes(&self, card: &anki_connect::CardInfoDto) -> bool {
+        match self {
+            ScheduleAction::Suspend => card.queue != -1,
+            ScheduleAction::Unsuspend => card.queue == -1,
+            ScheduleAction::Bury => !matches!(card.queue, -3 | -2),
+            ScheduleAction::Forget => card.card_type != 0,
+            ScheduleAction::SetDueDate => true,
+        }
+    }
+}
+
+/// Checks a due date in Anki's "Set Due Date" syntax: "N", "N-M", optionally followed by "!".
+fn validate_due_days(days: &str) -> Result<()> {
+    let range = days.strip_suffix('!').unwrap_or(days);
+    let valid = range
+        .split('-')
+        .map(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>();
+
+    if valid.is_empty() || valid.len() > 2 || valid.contains(&false) {
+        return Err(InvalidArguments::field("days", format!("invalid due date '{}': expected e.g. \"0\", \"3\", \"1-7\" or \"7!\"", days)).into());
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduleCardsResponse {
+    pub action: String,
+    pub matched: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub changed_card_ids: Vec<u64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleCardsArgs {
+    /// IDs of the cards to change (use either this or 'query')
+    pub card_ids: Option<Vec<u64>>,
+    /// Anki search query selecting the cards (use either this or 'card_ids')
+    pub query: Option<String>,
+    /// Allow changing more cards in one call than the server's limit (default: false)
+    #[serde(default)]
+    pub override_limit: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SetDueDateArgs {
+    /// IDs of the cards to change (use either this or 'query')
+    pub card_ids: Option<Vec<u64>>,
+    /// Anki search query selecting the cards (use either this or 'card_ids')
+    pub query: Option<String>,
+    /// Allow changing more cards in one call than the server's limit (default: false)
+    #[serde(default)]
+    pub override_limit: bool,
+    /// Days from today: "0" = today, "3" = in three days, "1-7" = random day in that range. Append "!" to also set the interval, e.g. "7!"
+    pub days: String,
+}
+
+/// One tool per scheduling action; they share card selection and the limit on
+/// how many cards a single call may change. [`SetDueDateTool`] covers
+/// [`ScheduleAction::SetDueDate`], which also needs the new due date.
+pub struct ScheduleCardsTool {
+    action: ScheduleAction,
+    max_cards: usize,
+}
+
+impl ScheduleCardsTool {
+    pub fn new(action: ScheduleAction, max_cards: usize) -> Self {
+        Self { action, max_cards }
+    }
+
+    async fn apply(
+        &self,
+        card_ids: Option<&[u64]>,
+        query: Option<&str>,
+        override_limit: bool,
+        days: Option<&str>,
+    ) -> Result<ScheduleCardsResponse> {
+        let card_ids = resolve_card_ids(card_ids, query)?;
+
+        if card_ids.len() > self.max_cards && !override_limit {
+            return Err(anyhow!(
+                "Selection matches {} cards, more than the limit of {}. Narrow the query or pass 'override_limit': true if this is intended",
+                card_ids.len(),
+                self.max_cards
+            ));
+        }
+
+        let cards = anki_connect::cards_info(&card_ids)
+            .map_err(|e| anyhow!("Failed to retrieve card details: {}", e))?;
+
+        let targets: Vec<u64> = cards.iter()
+            .filter(|c| self.action.changes(c))
+            .map(|c| c.card_id)
+            .collect();
+
+        if !targets.is_empty() {
+            let result = match self.action {
+                ScheduleAction::Suspend => anki_connect::suspend(&targets).map(|_| ()),
+                ScheduleAction::Unsuspend => anki_connect::unsuspend(&targets).map(|_| ()),
+                ScheduleAction::Forget => anki_connect::forget_cards(&targets),
+                ScheduleAction::SetDueDate => match days {
+                    Some(days) => anki_connect::set_due_date(&targets, days).map(|_| ()),
+                    None => return Err(anyhow!("Setting the due date needs 'days'")),
+                },
+                ScheduleAction::Bury => anki_connect::bury(&targets),
+            };
+            result.map_err(|e| anyhow!("Failed to {} cards: {}", self.action.name(), e))?;
+        }
+
+        tracing::info!("{}: changed {} of {} card(s)", self.action.name(), targets.len(), cards.len());
+
+        Ok(ScheduleCardsResponse {
+            action: self.action.name().to_string(),
+            matched: cards.len(),
+            changed: targets.len(),
+            unchanged: cards.len() - targets.len(),
+            changed_card_ids: targets,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for ScheduleCardsTool {
+    type Args = ScheduleCardsArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some(format!(
+                "{}. Select cards by ID or search query; refuses to change more than {} cards unless 'override_limit' is set. Reports how many cards changed",
+                self.action.description(),
+                self.max_cards
+            )),
+            output_schema: None,
+            annotations: Some(self.action.annotations()),
+        }
+    }
+
+    async fn call(&self, args: ScheduleCardsArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let result = self.apply(args.card_ids.as_deref(), args.query.as_deref(), args.override_limit, None).await;
+        json_result(result, &format!("running {}", self.action.name()))
+    }
+}
+
+/// The `set_due_date` tool: a [`ScheduleCardsTool`] that also takes the due date.
+pub struct SetDueDateTool(ScheduleCardsTool);
+
+impl SetDueDateTool {
+    pub fn new(max_cards: usize) -> Self {
+        Self(ScheduleCardsTool::new(ScheduleAction::SetDueDate, max_cards))
+    }
+}
+
+#[async_trait]
+impl TypedTool for SetDueDateTool {
+    type Args = SetDueDateArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        TypedTool::metadata(&self.0)
+    }
+
+    async fn call(&self, args: SetDueDateArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        validate_due_days(&args.days)?;
+
+        let result = self.0.apply(args.card_ids.as_deref(), args.query.as_deref(), args.override_limit, Some(&args.days)).await;
+        json_result(result, "running set_due_date")
+    }
+}
+
+const REVIEW_STATS_DEFAULT_DAYS: u64 = 30;
+pub(crate) const REVIEW_STATS_MAX_DAYS: u64 = 3650;
+const REVIEW_STATS_DEFAULT_FORECAST_DAYS: u64 = 7;
+const REVIEW_STATS_MAX_FORECAST_DAYS: u64 = 60;
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ReviewsByType {
+    pub learning: usize,
+    pub review: usize,
+    pub relearning: usize,
+    pub filtered: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DailyReviews {
+    /// 0 = the last 24 hours, 1 = the 24 hours before that, ...
+    pub days_ago: u64,
+    pub reviews: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ForecastDay {
+    /// 0 = today (including overdue cards), 1 = tomorrow, ...
+    pub day: u64,
+    pub due: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReviewStatsResponse {
+    pub query: String,
+    pub period_days: u64,
+    pub cards_in_selection: usize,
+    pub cards_reviewed: usize,
+    pub total_reviews: usize,
+    pub reviews_per_day: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_answer_seconds: Option<f64>,
+    /// Share of review-state answers that were not "again"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention_rate: Option<f64>,
+    /// Share of cards answered in review state that lapsed at least once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lapse_rate: Option<f64>,
+    pub reviews_by_type: ReviewsByType,
+    pub daily_reviews: Vec<DailyReviews>,
+    pub forecast: Vec<ForecastDay>,
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10_f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReviewStatsArgs {
+    /// Deck to analyse, including its subdecks (use either this or 'query')
+    pub deck: Option<String>,
+    /// Anki search query selecting the cards to analyse (use either this or 'deck')
+    pub query: Option<String>,
+    /// Length of the review history period in days (default: 30)
+    #[serde(default = "review_stats_default_days")]
+    #[schemars(range(min = 1, max = REVIEW_STATS_MAX_DAYS))]
+    pub days: u64,
+    /// Number of days to forecast due cards for (default: 7)
+    #[serde(default = "review_stats_default_forecast_days")]
+    #[schemars(range(max = REVIEW_STATS_MAX_FORECAST_DAYS))]
+    pub forecast_days: u64,
+}
+
+fn review_stats_default_days() -> u64 {
+    REVIEW_STATS_DEFAULT_DAYS
+}
+
+fn review_stats_default_forecast_days() -> u64 {
+    REVIEW_STATS_DEFAULT_FORECAST_DAYS
+}
+
+pub struct ReviewStatsTool;
+
+impl ReviewStatsTool {
+    pub(crate) async fn review_stats(
+        &self,
+        query: String,
+        days: u64,
+        forecast_days: u64,
+        context: &RequestContext,
+    ) -> Result<ReviewStatsResponse> {
+        // One step for the search, one for the review logs and one per forecast day
+        let steps = 2 + forecast_days as usize;
+
+        let card_ids = anki_connect::find_cards(&query)
+            .map_err(|e| anyhow!("Failed to search cards: {}", e))?;
+        context.report_progress(1, steps, format!("Found {} cards", card_ids.len()));
+
+        let logs = anki_connect::reviews_of_cards(&card_ids)
+            .map_err(|e| anyhow!("Failed to retrieve review logs: {}", e))?;
+        context.report_progress(2, steps, "Retrieved review logs");
+
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let day_ms = 86_400_000;
+        let since_ms = now_ms.saturating_sub(days.saturating_mul(day_ms));
+
+        let mut by_type = ReviewsByType::default();
+        let mut daily = vec![0usize; days as usize];
+        let mut total_reviews = 0;
+        let mut total_time_ms = 0;
+        let mut review_answers = 0;
+        let mut review_passed = 0;
+        let mut cards_reviewed = 0;
+        let mut review_cards = 0;
+        let mut lapsed_cards = 0;
+
+        for reviews in logs.values() {
+            let mut reviewed = false;
+            let mut in_review = false;
+            let mut lapsed = false;
+
+            // Manual rescheduling (type 4) and entries without an answer are not reviews
+            for review in reviews.iter().filter(|r| r.id >= since_ms && r.review_type != 4 && r.ease > 0) {
+                reviewed = true;
+                total_reviews += 1;
+                total_time_ms += review.time;
+
+                if let Some(count) = daily.get_mut((now_ms.saturating_sub(review.id) / day_ms) as usize) {
+                    *count += 1;
+                }
+
+                match review.review_type {
+                    0 => by_type.learning += 1,
+                    1 => {
+                        by_type.review += 1;
+                        in_review = true;
+                        review_answers += 1;
+                        if review.ease > 1 {
+                            review_passed += 1;
+                        } else {
+                            lapsed = true;
+                        }
+                    }
+                    2 => by_type.relearning += 1,
+                    _ => by_type.filtered += 1,
+                }
+            }
+
+            cards_reviewed += reviewed as usize;
+            review_cards += in_review as usize;
+            lapsed_cards += lapsed as usize;
+        }
+
+        let mut forecast = Vec::with_capacity(forecast_days as usize);
+        for day in 0..forecast_days {
+            let due_filter = if day == 0 { "prop:due<=0".to_string() } else { format!("prop:due={}", day) };
+            let due = anki_connect::find_cards(&format!("({}) {}", query, due_filter))
+                .map_err(|e| anyhow!("Failed to compute forecast: {}", e))?
+                .len();
+            forecast.push(ForecastDay { day, due });
+            context.report_progress(3 + day as usize, steps, format!("Forecast day {} of {}", day + 1, forecast_days));
+        }
+
+        let ratio = |part: usize, whole: usize| (whole > 0).then(|| round_to(part as f64 / whole as f64, 4));
+
+        Ok(ReviewStatsResponse {
+            query,
+            period_days: days,
+            cards_in_selection: card_ids.len(),
+            cards_reviewed,
+            total_reviews,
+            reviews_per_day: round_to(total_reviews as f64 / days as f64, 2),
+            average_answer_seconds: (total_reviews > 0)
+                .then(|| round_to(total_time_ms as f64 / total_reviews as f64 / 1000.0, 2)),
+            retention_rate: ratio(review_passed, review_answers),
+            lapse_rate: ratio(lapsed_cards, review_cards),
+            reviews_by_type: by_type,
+            daily_reviews: daily
+                .into_iter()
+                .enumerate()
+                .map(|(days_ago, reviews)| DailyReviews { days_ago: days_ago as u64, reviews })
+                .collect(),
+            forecast,
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for ReviewStatsTool {
+    type Args = ReviewStatsArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Computes review statistics for a deck or search query from the review history: retention rate, reviews per day, average answer time, lapse rate, daily review counts and a forecast of due cards".to_string()),
+            output_schema: Some(schema_for::<ReviewStatsResponse>()),
+            annotations: Some(ToolAnnotations::read_only()),
+        }
+    }
+
+    async fn call(&self, args: ReviewStatsArgs, context: &RequestContext) -> Result<CallToolResult> {
+        let query = match (args.deck, args.query) {
+            (Some(deck), None) => anki_connect::deck_query(&deck),
+            (None, Some(query)) => query,
+            _ => return Err(InvalidArguments::field("", "exactly one of 'deck' or 'query' must be provided").into()),
+        };
+
+        structured_json_result(self.review_stats(query, args.days, args.forecast_days, context).await, "computing review statistics")
+    }
+}
+
+const MEDIA_NOTES_CHUNK: usize = 1000;
+
+fn media_mime_type(filename: &str) -> &'static str {
+    let extension = filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "mp3" => "audio/mpeg",
+        "ogg" | "oga" => "audio/ogg",
+        "opus" => "audio/opus",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "flac" => "audio/flac",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "css" => "text/css",
+        "ttf" => "font/ttf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+fn validate_media_filename(filename: &str) -> Result<()> {
+    if filename.is_empty() || filename.contains('/') || filename.contains('\\') {
+        return Err(InvalidArguments::field("filename", format!("invalid file name '{}': expected a plain file name without directories", filename)).into());
+    }
+    Ok(())
+}
+
+/// Collects the media files a field or template refers to through `[sound:...]`
+/// tags, `src` attributes and CSS `url(...)`, the same references Anki's "Check
+/// Media" looks for. Names are HTML-unescaped, and `src`/`url` names percent-decoded.
+fn collect_media_references(text: &str, references: &mut std::collections::HashSet<String>) {
+    // Lowercasing ASCII keeps byte offsets, so indexes into `lower` are valid for `text`
+    let lower = text.to_ascii_lowercase();
+
+    let mut offset = 0;
+    while let Some(start) = lower[offset..].find("[sound:") {
+        let name_start = offset + start + "[sound:".len();
+        let Some(end) = text[name_start..].find(']') else { break };
+        references.insert(decode_html_entities(&text[name_start..name_start + end]));
+        offset = name_start + end;
+    }
+
+    for (marker, terminators) in [("src=", &['>'][..]), ("url(", &[')'][..])] {
+        let mut offset = 0;
+        while let Some(start) = lower[offset..].find(marker) {
+            let value_start = offset + start + marker.len();
+            let value = text[value_start..].trim_start();
+            let name = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+                _ => value.split(|c: char| c.is_whitespace() || terminators.contains(&c)).next().unwrap_or_default(),
+            };
+            if !name.is_empty() && !name.contains("://") && !name.starts_with("data:") {
+                references.insert(percent_decode(&decode_html_entities(name)));
+            }
+            offset = value_start;
+        }
+    }
+}
+
+/// Decodes the HTML character references Anki's editor writes into fields:
+/// the common named entities and numeric `&#NN;`/`&#xHH;` references.
+fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StoreMediaFileArgs {
+    /// Name to store the file under, e.g. "neko.mp3"
+    pub filename: String,
+    /// Base64-encoded file content (use either this or 'path')
+    pub data: Option<String>,
+    /// Absolute path of a local file the server may read (use either this or 'data')
+    pub path: Option<String>,
+    /// Replace an existing file with the same name (default: false)
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// Which local files `store_media_file` may read through its `path` argument.
+/// Anything it stores can be read back with `retrieve_media_file`, so a client
+/// allowed any path can read every file the server can.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaPathAccess {
+    /// Any file, for a single local client such as one on stdio.
+    Any,
+    /// Only files inside this directory.
+    Within(std::path::PathBuf),
+    /// No files; media must be sent as base64 data.
+    Disabled,
+}
+
+pub struct StoreMediaFileTool {
+    paths: MediaPathAccess,
+}
+
+impl StoreMediaFileTool {
+    pub fn new(paths: MediaPathAccess) -> Self {
+        Self { paths }
+    }
+
+    /// Resolves `path` to the file to read, refusing files the client may not read.
+    fn allowed_path(&self, path: &str) -> Result<std::path::PathBuf> {
+        match &self.paths {
+            MediaPathAccess::Any => Ok(path.into()),
+            MediaPathAccess::Within(dir) => {
+                // Resolving symlinks and '..' first, so neither can lead outside the directory
+                let resolved = std::fs::canonicalize(path)
+                    .map_err(|e| anyhow!("Cannot read '{}': {}", path, e))?;
+                if !resolved.starts_with(dir) {
+                    return Err(anyhow!("'{}' is outside the media directory {}", path, dir.display()));
+                }
+                Ok(resolved)
+            }
+            MediaPathAccess::Disabled => Err(anyhow!("This server does not read local files; send the file content as 'data' instead")),
+        }
+    }
+
+    async fn store(&self, filename: &str, data: Option<&str>, path: Option<&str>, overwrite: bool) -> Result<serde_json::Value> {
+        let client = anki_connect::client();
+        validate_media_filename(filename)?;
+
+        let stored = match (data, path) {
+            (Some(data), None) => client.media().store_from_base64(data, filename, overwrite),
+            (None, Some(path)) => client.media().store_from_path(self.allowed_path(path)?, filename, overwrite),
+            _ => return Err(InvalidArguments::field("", "exactly one of 'data' or 'path' must be provided").into()),
+        }
+        .map_err(|e| anyhow!("Failed to store media file '{}': {}", filename, e))?;
+
+        tracing::info!("Stored media file '{}'", stored);
+
+        // Anki may rename the file to avoid clashes, so reference it by the returned name
+        Ok(serde_json::json!({
+            "filename": stored,
+            "mime_type": media_mime_type(&stored),
+            "html": if media_mime_type(&stored).starts_with("image/") {
+                format!("<img src=\"{}\">", stored)
+            } else {
+                format!("[sound:{}]", stored)
+            },
+        }))
+    }
+}
+
+#[async_trait]
+impl TypedTool for StoreMediaFileTool {
+    type Args = StoreMediaFileArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some(format!(
+                "Stores a media file (image, audio, ...) in Anki's media folder from {}. Returns the stored file name and the HTML to reference it from a note field",
+                match &self.paths {
+                    MediaPathAccess::Any => "base64 data or a local file path".to_string(),
+                    MediaPathAccess::Within(dir) => format!("base64 data or the path of a file in {}", dir.display()),
+                    MediaPathAccess::Disabled => "base64 data".to_string(),
+                },
+            )),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: StoreMediaFileArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let result = self.store(&args.filename, args.data.as_deref(), args.path.as_deref(), args.overwrite).await;
+        json_result(result, "storing media file")
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RetrieveMediaFileArgs {
+    /// Name of the media file, e.g. "neko.jpg"
+    pub filename: String,
+}
+
+pub struct RetrieveMediaFileTool;
+
+impl RetrieveMediaFileTool {
+    async fn retrieve(&self, filename: &str) -> Result<ToolResultContent> {
+        validate_media_filename(filename)?;
+
+        let data = anki_connect::retrieve_media_file(filename)
+            .map_err(|e| anyhow!("Failed to retrieve media file '{}': {}", filename, e))?
+            .ok_or_else(|| anyhow!("Media file '{}' not found", filename))?;
+
+        let mime_type = media_mime_type(filename).to_string();
+
+        Ok(if mime_type.starts_with("image/") {
+            ToolResultContent::Image { data, mime_type }
+        } else {
+            ToolResultContent::Resource {
+                resource: ResourceReference {
+                    uri: format!("anki://media/{}", filename),
+                    mime_type: Some(mime_type),
+                    text: None,
+                    blob: Some(data),
+                },
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for RetrieveMediaFileTool {
+    type Args = RetrieveMediaFileArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Fetches a file from Anki's media folder. Images are returned as image content, other files as an embedded base64 resource".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
+        }
+    }
+
+    async fn call(&self, args: RetrieveMediaFileArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        match self.retrieve(&args.filename).await {
+            Ok(content) => Ok(CallToolResult {
+                content: vec![content],
+                is_error: Some(false),
+                structured_content: None,
+            }),
+            Err(e) if e.is::<InvalidArguments>() => Err(e),
+            Err(e) => Ok(error_result(format!("Error retrieving media file: {}", e))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MediaFileInfo {
+    pub filename: String,
+    pub mime_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListMediaFilesResponse {
+    pub pattern: String,
+    pub total: usize,
+    pub files: Vec<MediaFileInfo>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListMediaFilesArgs {
+    /// Glob pattern, e.g. "*.mp3" or "vocab_*" (default: "*")
+    #[serde(default = "all_media_files")]
+    pub pattern: String,
+}
+
+fn all_media_files() -> String {
+    "*".to_string()
+}
+
+pub struct ListMediaFilesTool;
+
+impl ListMediaFilesTool {
+    async fn list(&self, pattern: &str) -> Result<ListMediaFilesResponse> {
+        let mut names = anki_connect::media_file_names(pattern)
+            .map_err(|e| anyhow!("Failed to list media files: {}", e))?;
+        names.sort();
+
+        Ok(ListMediaFilesResponse {
+            pattern: pattern.to_string(),
+            total: names.len(),
+            files: names
+                .into_iter()
+                .map(|filename| MediaFileInfo {
+                    mime_type: media_mime_type(&filename).to_string(),
+                    filename,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl TypedTool for ListMediaFilesTool {
+    type Args = ListMediaFilesArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Lists files in Anki's media folder whose names match a glob pattern".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
+        }
+    }
+
+    async fn call(&self, args: ListMediaFilesArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        json_result(self.list(&args.pattern).await, "listing media files")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteUnusedMediaResponse {
+    pub pattern: String,
+    pub checked_files: usize,
+    pub unused_files: Vec<String>,
+    pub deleted: bool,
+    /// Token to pass back with `confirm: true` to delete exactly `unused_files`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in_secs: Option<u64>,
+}
+
+struct PendingMediaDeletion {
+    pattern: String,
+    files: Vec<String>,
+    created_at: std::time::Instant,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteUnusedMediaArgs {
+    /// Only consider files matching this glob pattern (default: "*")
+    #[serde(default = "all_media_files")]
+    pub pattern: String,
+    /// Actually delete the unused files (default: false, which only lists them)
+    #[serde(default)]
+    pub confirm: bool,
+    /// Token returned by the dry run; required when 'confirm' is true
+    pub preview_token: Option<String>,
+}
+
+pub struct DeleteUnusedMediaTool {
+    pending: std::sync::Mutex<HashMap<String, PendingMediaDeletion>>,
+}
+
+impl DeleteUnusedMediaTool {
+    pub fn new() -> Self {
+        Self {
+            pending: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lists the files matching `pattern` that no note or note type refers to.
+    fn find_unused(&self, pattern: &str) -> Result<(usize, Vec<String>)> {
+        let files = anki_connect::media_file_names(pattern)
+            .map_err(|e| anyhow!("Failed to list media files: {}", e))?;
+
+        let note_ids = anki_connect::find_notes("deck:*")
+            .map_err(|e| anyhow!("Failed to search notes: {}", e))?;
+
+        let mut references = std::collections::HashSet::new();
+        for chunk in note_ids.chunks(MEDIA_NOTES_CHUNK) {
+            let notes = anki_connect::notes_info(chunk)
+                .map_err(|e| anyhow!("Failed to retrieve note details: {}", e))?;
+            for note in &notes {
+                for field in note.fields.values() {
+                    collect_media_references(&field.value, &mut references);
+                }
+            }
+        }
+
+        // Templates and styling can refer to files directly, e.g. a logo on every card
+        let model_names: Vec<String> = anki_connect::model_names_and_ids()
+            .map_err(|e| anyhow!("Failed to list note types: {}", e))?
+            .into_keys()
+            .collect();
+        let models = anki_connect::find_models_by_name(&model_names)
+            .map_err(|e| anyhow!("Failed to retrieve note types: {}", e))?;
+        for model in &models {
+            for template in &model.tmpls {
+                collect_media_references(&template.qfmt, &mut references);
+                collect_media_references(&template.afmt, &mut references);
+            }
+            collect_media_references(&model.css, &mut references);
+        }
+
+        // Files starting with an underscore are referenced from templates or CSS and
+        // are never considered unused, matching Anki's own media check
+        let mut unused: Vec<String> = files
+            .iter()
+            .filter(|f| !f.starts_with('_') && !references.contains(*f))
+            .cloned()
+            .collect();
+        unused.sort();
+
+        Ok((files.len(), unused))
+    }
+
+    async fn preview(&self, pattern: &str) -> Result<DeleteUnusedMediaResponse> {
+        let (checked_files, unused) = self.find_unused(pattern)?;
+
+        let token = uuid::Uuid::new_v4().to_string();
+        {
+            let mut pending = self.pending.lock().map_err(|_| anyhow!("Preview token store is poisoned"))?;
+            pending.retain(|_, p| p.created_at.elapsed().as_secs() < DELETE_PREVIEW_TTL_SECS);
+            pending.insert(token.clone(), PendingMediaDeletion {
+                pattern: pattern.to_string(),
+                files: unused.clone(),
+                created_at: std::time::Instant::now(),
+            });
+        }
+
+        Ok(DeleteUnusedMediaResponse {
+            pattern: pattern.to_string(),
+            checked_files,
+            unused_files: unused,
+            deleted: false,
+            preview_token: Some(token),
+            expires_in_secs: Some(DELETE_PREVIEW_TTL_SECS),
+        })
+    }
+
+    async fn delete(&self, pattern: &str, token: &str) -> Result<DeleteUnusedMediaResponse> {
+        // Look the files up again so a file that became used since the preview is never deleted
+        let (checked_files, unused) = self.find_unused(pattern)?;
+
+        let mut pending = self.pending.lock()
+            .map_err(|_| anyhow!("Preview token store is poisoned"))?;
+
+        let preview = pending.get(token)
+            .ok_or_else(|| anyhow!("Unknown or already used preview token. Run delete_unused_media without 'confirm' to get a new one"))?;
+
+        if preview.created_at.elapsed().as_secs() >= DELETE_PREVIEW_TTL_SECS {
+            pending.remove(token);
+            return Err(anyhow!("Preview token has expired. Run delete_unused_media without 'confirm' to get a new one"));
+        }
+
+        if preview.pattern != pattern || preview.files != unused {
+            return Err(anyhow!("The unused files differ from the previewed files. Run delete_unused_media without 'confirm' to get a new preview"));
+        }
+
+        for filename in &unused {
+            anki_connect::delete_media_file(filename)
+                .map_err(|e| anyhow!("Failed to delete media file '{}': {}", filename, e))?;
+        }
+        pending.remove(token);
+
+        tracing::info!("Deleted {} unused media file(s)", unused.len());
+
+        Ok(DeleteUnusedMediaResponse {
+            pattern: pattern.to_string(),
+            checked_files,
+            unused_files: unused,
+            deleted: true,
+            preview_token: None,
+            expires_in_secs: None,
+        })
+    }
+}
+
+impl Default for DeleteUnusedMediaTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TypedTool for DeleteUnusedMediaTool {
+    type Args = DeleteUnusedMediaArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
+            description: Some("Finds media files that no note or note type references and deletes them. Without 'confirm' this is a dry run that lists the unused files and returns a preview_token; call again with the same pattern, confirm: true and that token to delete exactly those files. Deletion cannot be undone".to_string()),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::destructive()),
+        }
+    }
+
+    async fn call(&self, args: DeleteUnusedMediaArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        if args.confirm && args.preview_token.is_none() {
+            return Err(InvalidArguments::field("preview_token", "required when 'confirm' is true; run delete_unused_media without 'confirm' first").into());
+        }
+
+        match args.preview_token.as_deref().filter(|_| args.confirm) {
+            Some(token) => json_result(self.delete(&args.pattern, token).await, "deleting unused media"),
+            None => json_result(self.preview(&args.pattern).await, "deleting unused media"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn references(text: &str) -> HashSet<String> {
+        let mut references = HashSet::new();
+        collect_media_references(text, &mut references);
+        references
+    }
+
+    fn set(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn validate_due_days_accepts_anki_syntax() {
+        for days in ["0", "3", "1-7", "7!", "1-7!", "365"] {
+            assert!(validate_due_days(days).is_ok(), "{}", days);
+        }
+    }
+
+    #[test]
+    fn validate_due_days_rejects_everything_else() {
+        for days in ["", "!", "-1", "1-", "1-2-3", "a", "3d", "1 - 7", "7!!", "+3"] {
+            assert!(validate_due_days(days).is_err(), "{}", days);
+        }
+    }
+
+    fn field_names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn check(fields: &[&str], operations: serde_json::Value) -> Result<()> {
+        let operations: Vec<FieldOperation> = serde_json::from_value(operations).unwrap();
+        EditNoteTypeFieldsTool.check_operations(&field_names(fields), &operations)
+    }
+
+    #[test]
+    fn check_operations_replays_operations_in_order() {
+        let result = check(&["Front", "Back"], serde_json::json!([
+            { "op": "add", "name": "Extra" },
+            { "op": "rename", "name": "Extra", "new_name": "Notes" },
+            { "op": "reposition", "name": "Notes", "index": 0 },
+            { "op": "remove", "name": "Back" },
+            { "op": "add", "name": "Back", "index": 2 },
+        ]));
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn check_operations_reports_the_failing_step() {
+        let error = check(&["Front", "Back"], serde_json::json!([
+            { "op": "add", "name": "Extra" },
+            { "op": "rename", "name": "Missing", "new_name": "Other" },
+        ]))
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Operation 1: Field 'Missing' does not exist");
+    }
+
+    #[test]
+    fn check_operations_rejects_invalid_operations() {
+        let invalid = [
+            serde_json::json!([{ "op": "add", "name": "Front" }]),
+            serde_json::json!([{ "op": "add", "name": " " }]),
+            serde_json::json!([{ "op": "add", "name": "Extra", "index": 3 }]),
+            serde_json::json!([{ "op": "rename", "name": "Front", "new_name": "Back" }]),
+            serde_json::json!([{ "op": "rename", "name": "Front", "new_name": "" }]),
+            serde_json::json!([{ "op": "reposition", "name": "Front", "index": 2 }]),
+            serde_json::json!([{ "op": "remove", "name": "Front" }, { "op": "remove", "name": "Back" }]),
+        ];
+        for operations in invalid {
+            assert!(check(&["Front", "Back"], operations.clone()).is_err(), "{}", operations);
+        }
+    }
+
+    #[test]
+    fn collect_media_references_finds_sound_tags_in_any_case() {
+        assert_eq!(references("[sound:a.mp3] and [SOUND:b.mp3][Sound:c.ogg]"), set(&["a.mp3", "b.mp3", "c.ogg"]));
+        assert_eq!(references("[sound:unterminated.mp3"), set(&[]));
+    }
+
+    #[test]
+    fn collect_media_references_finds_src_attributes() {
+        assert_eq!(
+            references(r#"<img src="a.png"><IMG SRC='b c.png'><img src=d.png alt=x><img src=e.png>"#),
+            set(&["a.png", "b c.png", "d.png", "e.png"])
+        );
+    }
+
+    #[test]
+    fn collect_media_references_skips_remote_and_inline_sources() {
+        assert_eq!(references(r#"<img src="https://example.com/a.png"><img src="data:image/png;base64,AA==">"#), set(&[]));
+    }
+
+    #[test]
+    fn collect_media_references_decodes_entities_and_percent_escapes() {
+        assert_eq!(
+            references(r#"<img src="caf%C3%A9&amp;co.png">[sound:a&amp;b.mp3]<img src="&#x41;&#66;.png">"#),
+            set(&["café&co.png", "a&b.mp3", "AB.png"])
+        );
+    }
+
+    #[test]
+    fn collect_media_references_finds_css_urls() {
+        assert_eq!(
+            references(".card { background: url('bg%20x.jpg'); } @font-face { src: url(_font.ttf) }"),
+            set(&["bg x.jpg", "_font.ttf"])
+        );
+    }
+
+    #[test]
+    fn decode_html_entities_keeps_unknown_references() {
+        assert_eq!(decode_html_entities("a &unknown; b & c &#xZZ; &amp"), "a &unknown; b & c &#xZZ; &amp");
+        assert_eq!(decode_html_entities("&lt;&gt;&quot;&apos;&nbsp;&#233;"), "<>\"'\u{a0}é");
+    }
+}
@@ -1,16 +1,100 @@
 use async_trait::async_trait;
 use anyhow::Result;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use std::collections::HashMap;
 use crate::protocol::*;
+use crate::server::context::RequestContext;
+use crate::server::schema::{parse_arguments, schema_for};
 
 #[async_trait]
 pub trait Tool {
     fn definition(&self, name: String) -> crate::protocol::Tool;
     async fn call(&self, arguments: HashMap<String, serde_json::Value>) -> Result<CallToolResult>;
+
+    /// Runs the tool with access to its request, e.g. to stop early when the
+    /// client cancels. Tools that don't need it only implement `call`.
+    async fn call_with_context(
+        &self,
+        arguments: HashMap<String, serde_json::Value>,
+        _context: &RequestContext,
+    ) -> Result<CallToolResult> {
+        self.call(arguments).await
+    }
+}
+
+/// A tool whose arguments are a Rust type. Its input schema is generated from
+/// `Args`, and arguments are checked against that schema and deserialized
+/// before `call` runs, so the two cannot drift apart. Every `TypedTool` is a
+/// [`Tool`]; bad arguments fail with [`InvalidArguments`](crate::server::schema::InvalidArguments).
+#[async_trait]
+pub trait TypedTool {
+    type Args: DeserializeOwned + JsonSchema + Send + 'static;
+
+    fn metadata(&self) -> ToolMetadata;
+    async fn call(&self, args: Self::Args, context: &RequestContext) -> Result<CallToolResult>;
+}
+
+/// The parts of a [`TypedTool`]'s definition it provides itself; the name is
+/// the one it is registered under and the input schema comes from its `Args`.
+#[derive(Debug, Clone, Default)]
+pub struct ToolMetadata {
+    pub description: Option<String>,
+    pub output_schema: Option<serde_json::Value>,
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// `Args` for a tool that takes no arguments.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+// An empty `properties` makes unexpected arguments fail as unknown properties
+#[schemars(extend("properties" = {}))]
+pub struct NoArguments {}
+
+#[async_trait]
+impl<T: TypedTool + Sync> Tool for T {
+    fn definition(&self, name: String) -> crate::protocol::Tool {
+        let metadata = self.metadata();
+        crate::protocol::Tool {
+            name,
+            description: metadata.description,
+            input_schema: Some(schema_for::<T::Args>()),
+            output_schema: metadata.output_schema,
+            annotations: metadata.annotations,
+        }
+    }
+
+    async fn call(&self, arguments: HashMap<String, serde_json::Value>) -> Result<CallToolResult> {
+        self.call_with_context(arguments, &RequestContext::default()).await
+    }
+
+    async fn call_with_context(
+        &self,
+        arguments: HashMap<String, serde_json::Value>,
+        context: &RequestContext,
+    ) -> Result<CallToolResult> {
+        let args = parse_arguments::<T::Args>(arguments)?;
+        TypedTool::call(self, args, context).await
+    }
+}
+
+#[async_trait]
+pub trait Prompt {
+    fn definition(&self, name: String) -> crate::protocol::Prompt;
+    async fn get(&self, arguments: HashMap<String, String>) -> Result<GetPromptResult>;
 }
 
 #[async_trait]
 pub trait Resource {
     fn definition(&self, uri: String) -> crate::protocol::Resource;
     async fn read(&self) -> Result<Vec<ResourceContent>>;
-}
\ No newline at end of file
+}
+
+/// A family of resources addressed by an RFC 6570 URI template such as
+/// `anki://deck/{name}`, resolved when a matching URI is read.
+#[async_trait]
+pub trait ResourceTemplate {
+    fn definition(&self, uri_template: String) -> crate::protocol::ResourceTemplate;
+    async fn read(&self, uri: &str, params: HashMap<String, String>) -> Result<Vec<ResourceContent>>;
+}
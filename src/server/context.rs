@@ -0,0 +1,75 @@
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use crate::protocol::*;
+use crate::server::Outgoing;
+
+/// Per-request state handed to tools, through which they learn that the
+/// client cancelled the request and report progress on long operations.
+#[derive(Clone, Default)]
+pub struct RequestContext {
+    cancellation: CancellationToken,
+    progress: Option<ProgressSink>,
+}
+
+/// Where progress notifications for a request go, present only when the
+/// client sent a progress token with it.
+#[derive(Clone)]
+struct ProgressSink {
+    token: serde_json::Value,
+    with_message: bool,
+    outgoing: mpsc::UnboundedSender<Outgoing>,
+}
+
+impl RequestContext {
+    pub(crate) fn new(
+        cancellation: CancellationToken,
+        progress_token: Option<serde_json::Value>,
+        version: ProtocolVersion,
+        outgoing: mpsc::UnboundedSender<Outgoing>,
+    ) -> Self {
+        Self {
+            cancellation,
+            progress: progress_token.map(|token| ProgressSink {
+                token,
+                with_message: version.supports_progress_messages(),
+                outgoing,
+            }),
+        }
+    }
+
+    /// Whether the client sent `notifications/cancelled` for this request.
+    /// Long-running tools check this between steps and stop early, returning
+    /// what they have done so far.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Completes once the request is cancelled.
+    pub async fn cancelled(&self) {
+        self.cancellation.cancelled().await
+    }
+
+    /// Sends a `notifications/progress` for this request if the client asked
+    /// for progress and has not cancelled the request; otherwise does nothing.
+    /// `progress` must increase with every call.
+    pub fn report_progress(&self, progress: usize, total: usize, message: impl Into<String>) {
+        let Some(sink) = self.progress.as_ref().filter(|_| !self.is_cancelled()) else {
+            return;
+        };
+
+        let notification = ProgressNotification {
+            progress_token: sink.token.clone(),
+            progress: progress as f64,
+            total: Some(total as f64),
+            message: sink.with_message.then(|| message.into()),
+        };
+
+        let _ = sink.outgoing.send(Outgoing::Message(JsonRpcMessage {
+            jsonrpc: "2.0".to_string(),
+            content: MessageContent::Notification(Notification {
+                method: "notifications/progress".to_string(),
+                params: serde_json::to_value(notification).ok(),
+            }),
+        }));
+    }
+}
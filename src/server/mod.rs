@@ -1,81 +1,302 @@
 pub mod capabilities;
+pub mod context;
 pub mod tools;
 pub mod resources;
+pub mod schema;
+pub mod subscriptions;
 pub mod anki_tools;
+pub mod anki_connect;
+pub mod anki_resources;
+pub mod anki_prompts;
 
 use anyhow::Result;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 use crate::protocol::*;
-use crate::transport::Transport;
-use crate::server::capabilities::{Tool, Resource};
-
+use crate::transport::{InvalidMessage, Listener, Transport};
+use crate::server::capabilities::{Tool, Prompt, Resource, ResourceTemplate};
+use crate::server::context::RequestContext;
+use crate::server::resources::ResourceRegistry;
+use crate::server::schema::InvalidArguments;
+use crate::server::subscriptions::{fingerprint, Subscriptions, DEFAULT_POLL_INTERVAL};
+
+/// An MCP server session. Clones share the session state, which lets
+/// requests run on their own tasks; use [`McpServer::new_session`] for a
+/// separate client.
+#[derive(Clone)]
 pub struct McpServer {
-    initialized: bool,
-    tools: HashMap<String, Box<dyn Tool + Send + Sync>>,
-    resources: HashMap<String, Box<dyn Resource + Send + Sync>>,
+    /// The version agreed on in `initialize`; `None` until the client has initialized.
+    protocol_version: Arc<Mutex<Option<ProtocolVersion>>>,
+    tools: Arc<HashMap<String, Arc<dyn Tool + Send + Sync>>>,
+    prompts: Arc<HashMap<String, Arc<dyn Prompt + Send + Sync>>>,
+    resources: Arc<ResourceRegistry>,
+    /// Identifies this session in the subscriptions shared by all sessions.
+    session: u64,
+    subscriptions: Subscriptions,
+    in_flight: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    /// The least severe level sent as `notifications/message`, set by `logging/setLevel`.
+    log_level: Arc<Mutex<LoggingLevel>>,
+    poll_interval: Duration,
+}
+
+/// What running requests hand back to [`McpServer::run`] for the transport.
+pub(crate) enum Outgoing {
+    Message(JsonRpcMessage),
+    /// A cancelled request that will get no response.
+    Dropped(serde_json::Value),
 }
 
 impl McpServer {
     pub fn new() -> Self {
         Self {
-            initialized: false,
-            tools: HashMap::new(),
-            resources: HashMap::new(),
+            protocol_version: Arc::default(),
+            tools: Arc::new(HashMap::new()),
+            prompts: Arc::new(HashMap::new()),
+            resources: Arc::new(ResourceRegistry::default()),
+            session: next_session_id(),
+            subscriptions: Subscriptions::default(),
+            in_flight: Arc::default(),
+            log_level: Arc::default(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
         }
     }
 
     pub fn add_tool(&mut self, name: String, tool: Box<dyn Tool + Send + Sync>) {
-        self.tools.insert(name, tool);
+        Arc::make_mut(&mut self.tools).insert(name, tool.into());
+    }
+
+    pub fn add_prompt(&mut self, name: String, prompt: Box<dyn Prompt + Send + Sync>) {
+        Arc::make_mut(&mut self.prompts).insert(name, prompt.into());
     }
 
     pub fn add_resource(&mut self, uri: String, resource: Box<dyn Resource + Send + Sync>) {
-        self.resources.insert(uri, resource);
+        Arc::make_mut(&mut self.resources).add_resource(uri, resource);
     }
 
-    pub async fn run<T: Transport>(&mut self, mut transport: T) -> Result<()> {
-        tracing::info!("Starting MCP server");
+    pub fn add_resource_template(&mut self, uri_template: String, template: Box<dyn ResourceTemplate + Send + Sync>) {
+        Arc::make_mut(&mut self.resources).add_template(uri_template, template);
+    }
+
+    /// Sets how often subscribed resources are re-read to detect changes.
+    pub fn set_subscription_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
 
+    /// Creates a server for another client: it shares the tools, prompts and
+    /// resources of this one but has its own initialization and subscriptions.
+    /// Subscribed resources are polled once for all sessions.
+    pub fn new_session(&self) -> Self {
+        Self {
+            protocol_version: Arc::default(),
+            tools: self.tools.clone(),
+            prompts: self.prompts.clone(),
+            resources: self.resources.clone(),
+            session: next_session_id(),
+            subscriptions: self.subscriptions.clone(),
+            in_flight: Arc::default(),
+            log_level: Arc::default(),
+            poll_interval: self.poll_interval,
+        }
+    }
+
+    /// Accepts clients from `listener` until it fails, running each one in its own session.
+    pub async fn serve<L: Listener>(&self, mut listener: L) -> Result<()> {
         loop {
-            match transport.read_message().await {
-                Ok(message) => {
-                    if let Some(response) = self.handle_message(message).await? {
-                        transport.write_message(response).await?;
-                    }
+            let transport = listener.accept().await?;
+            let session = self.new_session();
+
+            tokio::spawn(async move {
+                if let Err(e) = session.run(transport).await {
+                    tracing::warn!("Session ended with an error: {}", e);
                 }
-                Err(e) => {
-                    tracing::error!("Error reading message: {}", e);
-                    break;
+            });
+        }
+    }
+
+    /// Serves one client until its transport closes. Requests run concurrently
+    /// and their responses are written as they complete; once the transport
+    /// closes, requests still in flight are answered before returning.
+    pub async fn run<T: Transport>(&self, mut transport: T) -> Result<()> {
+        tracing::info!("Starting MCP server");
+
+        let mut updates = self.subscriptions.register(self.session);
+        self.subscriptions.start_polling(self.resources.clone(), self.poll_interval);
+
+        // Every running request holds a sender, so once reading stops and
+        // this one is dropped the channel closes after the last response
+        let (outgoing_tx, mut outgoing) = mpsc::unbounded_channel();
+        let mut outgoing_tx = Some(outgoing_tx);
+
+        let result = loop {
+            tokio::select! {
+                message = transport.read_message(), if outgoing_tx.is_some() => match message {
+                    Ok(message) => {
+                        if let Some(outgoing_tx) = &outgoing_tx {
+                            self.dispatch(message, outgoing_tx).await;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Error reading message: {}", e);
+                        outgoing_tx = None;
+                        if e.is::<InvalidMessage>() {
+                            if let Err(e) = transport.write_message(parse_error_response()).await {
+                                break Err(e);
+                            }
+                        }
+                    }
+                },
+                message = outgoing.recv() => match message {
+                    Some(Outgoing::Message(message)) => {
+                        if let Err(e) = transport.write_message(message).await {
+                            break Err(e);
+                        }
+                    }
+                    Some(Outgoing::Dropped(id)) => transport.request_dropped(&id),
+                    None => break Ok(()),
+                },
+                Some(uri) = updates.recv() => {
+                    tracing::info!("Resource updated: {}", uri);
+                    if let Err(e) = transport.write_message(resource_updated_notification(uri)).await {
+                        break Err(e);
+                    }
                 }
             }
-        }
+        };
 
-        Ok(())
+        self.subscriptions.unregister(self.session);
+        transport.close().await;
+        result
     }
 
-    async fn handle_message(&mut self, message: JsonRpcMessage) -> Result<Option<JsonRpcMessage>> {
+    /// Handles a message from the client, sending any response to `outgoing`.
+    /// Notifications and `initialize` are handled in arrival order since the
+    /// messages after them depend on their effect; other requests run on their
+    /// own task and can be cancelled by the client.
+    async fn dispatch(&self, message: JsonRpcMessage, outgoing: &mpsc::UnboundedSender<Outgoing>) {
         match message.content {
+            MessageContent::Request(request) if request.method == "initialize" => {
+                let response = self.handle_request(request, RequestContext::default()).await;
+                let _ = outgoing.send(Outgoing::Message(response));
+            }
             MessageContent::Request(request) => {
-                let response = self.handle_request(request).await;
-                Ok(Some(response))
+                let id = request.id.clone();
+                let key = id.to_string();
+                let progress_token = RequestMeta::from_params(&request.params).progress_token;
+                let version = self.protocol_version().unwrap_or(ProtocolVersion::LATEST);
+                let cancellation = CancellationToken::new();
+                self.in_flight.lock().unwrap().insert(key.clone(), cancellation.clone());
+
+                let server = self.clone();
+                let outgoing = outgoing.clone();
+                let runtime = tokio::runtime::Handle::current();
+
+                // Tools talk to AnkiConnect over blocking HTTP, so each request
+                // gets a blocking thread rather than a runtime worker
+                tokio::task::spawn_blocking(move || {
+                    let context = RequestContext::new(cancellation.clone(), progress_token, version, outgoing.clone());
+                    let response = runtime.block_on(async {
+                        // A blocking tool body may finish after the cancellation
+                        // arrived; polling it first keeps its result to report
+                        tokio::select! {
+                            biased;
+                            response = server.handle_request(request, context) => Some(response),
+                            _ = cancellation.cancelled() => None,
+                        }
+                    });
+                    server.in_flight.lock().unwrap().remove(&key);
+
+                    // Cancelled requests get no response; a tool that stopped early
+                    // still reports what it got done before the cancellation
+                    match response {
+                        Some(response) if !cancellation.is_cancelled() => {
+                            let _ = outgoing.send(Outgoing::Message(response));
+                        }
+                        Some(response) => {
+                            tracing::info!("Request {} was cancelled, reporting its partial result", key);
+                            if let Some(message) = server.partial_result_message(&id, response) {
+                                let _ = outgoing.send(Outgoing::Message(message));
+                            }
+                            let _ = outgoing.send(Outgoing::Dropped(id));
+                        }
+                        None => {
+                            tracing::info!("Request {} was cancelled before it finished", key);
+                            let _ = outgoing.send(Outgoing::Dropped(id));
+                        }
+                    }
+                });
             }
             MessageContent::Notification(notification) => {
                 self.handle_notification(notification).await;
-                Ok(None)
             }
             MessageContent::Response(_) => {
                 tracing::warn!("Received unexpected response message");
-                Ok(None)
             }
         }
     }
 
-    async fn handle_request(&mut self, request: Request) -> JsonRpcMessage {
+    /// Wraps the response of a request that finished after it was cancelled in
+    /// a `notifications/message`, unless the client raised its log level above
+    /// `notice`.
+    fn partial_result_message(&self, id: &serde_json::Value, response: JsonRpcMessage) -> Option<JsonRpcMessage> {
+        let level = LoggingLevel::Notice;
+        if level < *self.log_level.lock().unwrap() {
+            return None;
+        }
+
+        let MessageContent::Response(response) = response.content else {
+            return None;
+        };
+        let mut data = serde_json::json!({
+            "message": "Request was cancelled, but had already done this",
+            "requestId": id,
+        });
+        if let Some(result) = response.result {
+            data["result"] = result;
+        }
+        if let Some(error) = response.error {
+            data["error"] = serde_json::to_value(error).unwrap_or_default();
+        }
+        let notification = LoggingMessageNotification {
+            level,
+            logger: Some("ankiconnect-mcp".to_string()),
+            data,
+        };
+
+        Some(JsonRpcMessage {
+            jsonrpc: "2.0".to_string(),
+            content: MessageContent::Notification(Notification {
+                method: "notifications/message".to_string(),
+                params: serde_json::to_value(notification).ok(),
+            }),
+        })
+    }
+
+    fn protocol_version(&self) -> Option<ProtocolVersion> {
+        *self.protocol_version.lock().unwrap()
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.protocol_version().is_some()
+    }
+
+    async fn handle_request(&self, request: Request, context: RequestContext) -> JsonRpcMessage {
         let result = match request.method.as_str() {
             "initialize" => self.handle_initialize(request.params).await,
+            "ping" => Ok(serde_json::json!({})),
+            "logging/setLevel" => self.handle_set_level(request.params),
             "tools/list" => self.handle_list_tools().await,
-            "tools/call" => self.handle_call_tool(request.params).await,
+            "tools/call" => self.handle_call_tool(request.params, &context).await,
+            "prompts/list" => self.handle_list_prompts().await,
+            "prompts/get" => self.handle_get_prompt(request.params).await,
             "resources/list" => self.handle_list_resources().await,
             "resources/read" => self.handle_read_resource(request.params).await,
+            "resources/templates/list" => self.handle_list_resource_templates().await,
+            "resources/subscribe" => self.handle_subscribe(request.params).await,
+            "resources/unsubscribe" => self.handle_unsubscribe(request.params).await,
             _ => Err(McpError::method_not_found()),
         };
 
@@ -102,32 +323,67 @@ impl McpServer {
         }
     }
 
-    async fn handle_notification(&mut self, notification: Notification) {
+    async fn handle_notification(&self, notification: Notification) {
         match notification.method.as_str() {
-            "initialized" => {
+            // The bare name is what earlier versions of this server expected
+            "notifications/initialized" | "initialized" => {
                 tracing::info!("Client sent initialized notification");
             }
+            "notifications/cancelled" => self.handle_cancelled(notification.params),
             _ => {
                 tracing::warn!("Unknown notification method: {}", notification.method);
             }
         }
     }
 
-    async fn handle_initialize(&mut self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
-        let _init_request: InitializeRequest = params
-            .ok_or_else(|| McpError::invalid_params())?
+    /// Cancels an in-flight request. Cancellations for requests that already
+    /// finished or were never seen are ignored, as they can race with the response.
+    fn handle_cancelled(&self, params: Option<serde_json::Value>) {
+        let Some(cancelled) = params.and_then(|p| CancelledNotification::try_from(p).ok()) else {
+            tracing::warn!("Ignoring malformed cancellation notification");
+            return;
+        };
+
+        let key = cancelled.request_id.to_string();
+        match self.in_flight.lock().unwrap().get(&key) {
+            Some(cancellation) => {
+                tracing::info!(
+                    "Client cancelled request {}: {}",
+                    key,
+                    cancelled.reason.as_deref().unwrap_or("no reason given")
+                );
+                cancellation.cancel();
+            }
+            None => tracing::debug!("Ignoring cancellation of unknown request {}", key),
+        }
+    }
+
+    async fn handle_initialize(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
+        let init_request: InitializeRequest = params
+            .ok_or_else(McpError::invalid_params)?
             .try_into()
             .map_err(|_| McpError::invalid_params())?;
 
-        self.initialized = true;
+        let version = ProtocolVersion::negotiate(&init_request.protocol_version);
+        tracing::info!(
+            "Client {} {} requested protocol {}, using {}",
+            init_request.client_info.name,
+            init_request.client_info.version,
+            init_request.protocol_version,
+            version
+        );
+
+        *self.protocol_version.lock().unwrap() = Some(version);
 
         let result = InitializeResult {
-            protocol_version: MCP_VERSION.to_string(),
+            protocol_version: version.to_string(),
             capabilities: ServerCapabilities {
-                logging: None,
-                prompts: None,
+                logging: Some(LoggingCapability {}),
+                prompts: Some(PromptsCapability {
+                    list_changed: Some(false),
+                }),
                 resources: Some(ResourcesCapability {
-                    subscribe: Some(false),
+                    subscribe: Some(true),
                     list_changed: Some(false),
                 }),
                 tools: Some(ToolsCapability {
@@ -143,72 +399,229 @@ impl McpServer {
         serde_json::to_value(result).map_err(|_| McpError::internal_error())
     }
 
+    fn handle_set_level(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
+        let set_level: SetLevelRequest = params
+            .ok_or_else(McpError::invalid_params)?
+            .try_into()
+            .map_err(|_| McpError::invalid_params())?;
+
+        tracing::info!("Client set log level to {:?}", set_level.level);
+        *self.log_level.lock().unwrap() = set_level.level;
+        Ok(serde_json::json!({}))
+    }
+
     async fn handle_list_tools(&self) -> Result<serde_json::Value, McpError> {
-        if !self.initialized {
+        let Some(version) = self.protocol_version() else {
             return Err(McpError::custom(-32002, "Server not initialized".to_string()));
-        }
+        };
 
         let tools: Vec<crate::protocol::Tool> = self.tools
             .iter()
-            .map(|(name, tool)| tool.definition(name.clone()))
+            .map(|(name, tool)| {
+                let mut definition = tool.definition(name.clone());
+                if !version.supports_tool_annotations() {
+                    definition.annotations = None;
+                }
+                if !version.supports_structured_content() {
+                    definition.output_schema = None;
+                }
+                definition
+            })
             .collect();
 
         let result = ListToolsResult { tools };
         serde_json::to_value(result).map_err(|_| McpError::internal_error())
     }
 
-    async fn handle_call_tool(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
-        if !self.initialized {
+    async fn handle_call_tool(&self, params: Option<serde_json::Value>, context: &RequestContext) -> Result<serde_json::Value, McpError> {
+        let Some(version) = self.protocol_version() else {
             return Err(McpError::custom(-32002, "Server not initialized".to_string()));
-        }
+        };
 
         let call_request: CallToolRequest = params
-            .ok_or_else(|| McpError::invalid_params())?
+            .ok_or_else(McpError::invalid_params)?
             .try_into()
             .map_err(|_| McpError::invalid_params())?;
 
         let tool = self.tools.get(&call_request.name)
             .ok_or_else(|| McpError::custom(-32601, format!("Tool '{}' not found", call_request.name)))?;
 
-        let result = tool.call(call_request.arguments.unwrap_or_default()).await
-            .map_err(|e| McpError::custom(-32603, format!("Tool execution failed: {}", e)))?;
+        let mut result = tool.call_with_context(call_request.arguments.unwrap_or_default(), context).await
+            .map_err(|e| match e.downcast_ref::<InvalidArguments>() {
+                Some(invalid) => invalid.to_mcp_error(),
+                None => McpError::custom(-32603, format!("Tool execution failed: {}", e)),
+            })?;
+
+        if !version.supports_structured_content() {
+            result.structured_content = None;
+        }
 
         serde_json::to_value(result).map_err(|_| McpError::internal_error())
     }
 
-    async fn handle_list_resources(&self) -> Result<serde_json::Value, McpError> {
-        if !self.initialized {
+    async fn handle_list_prompts(&self) -> Result<serde_json::Value, McpError> {
+        if !self.is_initialized() {
             return Err(McpError::custom(-32002, "Server not initialized".to_string()));
         }
 
-        let resources: Vec<crate::protocol::Resource> = self.resources
+        let prompts: Vec<crate::protocol::Prompt> = self.prompts
             .iter()
-            .map(|(uri, resource)| resource.definition(uri.clone()))
+            .map(|(name, prompt)| prompt.definition(name.clone()))
             .collect();
 
+        let result = ListPromptsResult { prompts };
+        serde_json::to_value(result).map_err(|_| McpError::internal_error())
+    }
+
+    async fn handle_get_prompt(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
+        if !self.is_initialized() {
+            return Err(McpError::custom(-32002, "Server not initialized".to_string()));
+        }
+
+        let get_request: GetPromptRequest = params
+            .ok_or_else(McpError::invalid_params)?
+            .try_into()
+            .map_err(|_| McpError::invalid_params())?;
+
+        let prompt = self.prompts.get(&get_request.name)
+            .ok_or_else(|| McpError::custom(-32602, format!("Prompt '{}' not found", get_request.name)))?;
+
+        let arguments = get_request.arguments.unwrap_or_default();
+
+        let definition = prompt.definition(get_request.name.clone());
+        if let Some(missing) = definition.arguments
+            .unwrap_or_default()
+            .into_iter()
+            .find(|arg| arg.required == Some(true) && !arguments.contains_key(&arg.name))
+        {
+            return Err(McpError::custom(-32602, format!("Missing required argument '{}'", missing.name)));
+        }
+
+        let result = prompt.get(arguments).await
+            .map_err(|e| McpError::custom(-32603, format!("Prompt generation failed: {}", e)))?;
+
+        serde_json::to_value(result).map_err(|_| McpError::internal_error())
+    }
+
+    async fn handle_list_resources(&self) -> Result<serde_json::Value, McpError> {
+        if !self.is_initialized() {
+            return Err(McpError::custom(-32002, "Server not initialized".to_string()));
+        }
+
+        let resources = self.resources.definitions();
+
         let result = ListResourcesResult { resources };
         serde_json::to_value(result).map_err(|_| McpError::internal_error())
     }
 
     async fn handle_read_resource(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
-        if !self.initialized {
+        if !self.is_initialized() {
             return Err(McpError::custom(-32002, "Server not initialized".to_string()));
         }
 
         let read_request: ReadResourceRequest = params
-            .ok_or_else(|| McpError::invalid_params())?
+            .ok_or_else(McpError::invalid_params)?
             .try_into()
             .map_err(|_| McpError::invalid_params())?;
 
-        let resource = self.resources.get(&read_request.uri)
-            .ok_or_else(|| McpError::custom(-32601, format!("Resource '{}' not found", read_request.uri)))?;
-
-        let contents = resource.read().await
+        let contents = self.resources.read(&read_request.uri).await
+            .ok_or_else(|| McpError::custom(-32601, format!("Resource '{}' not found", read_request.uri)))?
             .map_err(|e| McpError::custom(-32603, format!("Resource read failed: {}", e)))?;
 
         let result = ReadResourceResult { contents };
         serde_json::to_value(result).map_err(|_| McpError::internal_error())
     }
+
+    async fn handle_list_resource_templates(&self) -> Result<serde_json::Value, McpError> {
+        if !self.is_initialized() {
+            return Err(McpError::custom(-32002, "Server not initialized".to_string()));
+        }
+
+        let resource_templates = self.resources.template_definitions();
+
+        let result = ListResourceTemplatesResult { resource_templates };
+        serde_json::to_value(result).map_err(|_| McpError::internal_error())
+    }
+
+    async fn handle_subscribe(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
+        if !self.is_initialized() {
+            return Err(McpError::custom(-32002, "Server not initialized".to_string()));
+        }
+
+        let subscribe_request: SubscribeRequest = params
+            .ok_or_else(McpError::invalid_params)?
+            .try_into()
+            .map_err(|_| McpError::invalid_params())?;
+
+        if !self.resources.contains(&subscribe_request.uri) {
+            return Err(McpError::custom(-32601, format!("Resource '{}' not found", subscribe_request.uri)));
+        }
+
+        // Changes are reported relative to the contents at subscription time
+        let initial = match self.resources.read(&subscribe_request.uri).await {
+            Some(Ok(contents)) => Some(fingerprint(&contents)),
+            _ => None,
+        };
+
+        tracing::info!("Client subscribed to {}", subscribe_request.uri);
+        self.subscriptions.subscribe(self.session, subscribe_request.uri, initial);
+
+        Ok(serde_json::json!({}))
+    }
+
+    async fn handle_unsubscribe(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value, McpError> {
+        if !self.is_initialized() {
+            return Err(McpError::custom(-32002, "Server not initialized".to_string()));
+        }
+
+        let unsubscribe_request: UnsubscribeRequest = params
+            .ok_or_else(McpError::invalid_params)?
+            .try_into()
+            .map_err(|_| McpError::invalid_params())?;
+
+        tracing::info!("Client unsubscribed from {}", unsubscribe_request.uri);
+        self.subscriptions.unsubscribe(self.session, &unsubscribe_request.uri);
+
+        Ok(serde_json::json!({}))
+    }
+}
+
+/// The answer to a message that could not be parsed, which has no ID to respond to.
+fn parse_error_response() -> JsonRpcMessage {
+    let error = McpError::parse_error();
+    JsonRpcMessage {
+        jsonrpc: "2.0".to_string(),
+        content: MessageContent::Response(Response {
+            id: serde_json::Value::Null,
+            result: None,
+            error: Some(JsonRpcError {
+                code: error.code,
+                message: error.message,
+                data: error.data,
+            }),
+        }),
+    }
+}
+
+fn resource_updated_notification(uri: String) -> JsonRpcMessage {
+    JsonRpcMessage {
+        jsonrpc: "2.0".to_string(),
+        content: MessageContent::Notification(Notification {
+            method: "notifications/resources/updated".to_string(),
+            params: serde_json::to_value(ResourceUpdatedNotification { uri }).ok(),
+        }),
+    }
+}
+
+fn next_session_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+impl Default for McpServer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TryFrom<serde_json::Value> for InitializeRequest {
@@ -227,10 +640,50 @@ impl TryFrom<serde_json::Value> for CallToolRequest {
     }
 }
 
+impl TryFrom<serde_json::Value> for GetPromptRequest {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
 impl TryFrom<serde_json::Value> for ReadResourceRequest {
     type Error = serde_json::Error;
 
     fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
         serde_json::from_value(value)
     }
-}
\ No newline at end of file
+}
+
+impl TryFrom<serde_json::Value> for SubscribeRequest {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+impl TryFrom<serde_json::Value> for UnsubscribeRequest {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+impl TryFrom<serde_json::Value> for CancelledNotification {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+impl TryFrom<serde_json::Value> for SetLevelRequest {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
 use crate::protocol::*;
-use crate::server::capabilities::Resource;
+use crate::server::capabilities::{Resource, ResourceTemplate};
 
 pub struct StaticTextResource {
     name: String,
@@ -37,4 +39,195 @@ impl Resource for StaticTextResource {
             mime_type: Some("text/plain".to_string()),
         }])
     }
-}
\ No newline at end of file
+}
+
+/// Decodes `%XX` escapes, leaving malformed escapes untouched.
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Matches a URI against a template made of literal text and simple `{name}`
+/// expressions, returning the percent-decoded value of each expression.
+///
+/// An expression extends up to the next literal text of the template, so the
+/// last expression takes the rest of the URI (deck names may contain `/`).
+pub fn match_uri_template(template: &str, uri: &str) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    let mut template = template;
+    let mut uri = uri;
+
+    loop {
+        let Some(start) = template.find('{') else {
+            return (template == uri).then_some(params);
+        };
+
+        let literal = &template[..start];
+        uri = uri.strip_prefix(literal)?;
+
+        let end = template[start..].find('}')? + start;
+        let name = &template[start + 1..end];
+        template = &template[end + 1..];
+
+        let value_len = if template.is_empty() {
+            uri.len()
+        } else {
+            let next = template.find('{').unwrap_or(template.len());
+            // Adjacent expressions have no literal between them to split on
+            if next == 0 {
+                return None;
+            }
+            uri.find(&template[..next])?
+        };
+
+        let value = &uri[..value_len];
+        if value.is_empty() {
+            return None;
+        }
+        params.insert(name.to_string(), percent_decode(value));
+        uri = &uri[value_len..];
+    }
+}
+
+type SharedTemplate = Arc<dyn ResourceTemplate + Send + Sync>;
+
+/// The server's resources and resource templates. Cloning is cheap, so the
+/// subscription poller can resolve URIs exactly like `resources/read` does.
+#[derive(Clone, Default)]
+pub struct ResourceRegistry {
+    resources: HashMap<String, Arc<dyn Resource + Send + Sync>>,
+    templates: HashMap<String, SharedTemplate>,
+}
+
+impl ResourceRegistry {
+    pub fn add_resource(&mut self, uri: String, resource: Box<dyn Resource + Send + Sync>) {
+        self.resources.insert(uri, resource.into());
+    }
+
+    pub fn add_template(&mut self, uri_template: String, template: Box<dyn ResourceTemplate + Send + Sync>) {
+        self.templates.insert(uri_template, template.into());
+    }
+
+    pub fn definitions(&self) -> Vec<crate::protocol::Resource> {
+        self.resources
+            .iter()
+            .map(|(uri, resource)| resource.definition(uri.clone()))
+            .collect()
+    }
+
+    pub fn template_definitions(&self) -> Vec<crate::protocol::ResourceTemplate> {
+        self.templates
+            .iter()
+            .map(|(uri_template, template)| template.definition(uri_template.clone()))
+            .collect()
+    }
+
+    /// Returns whether a resource or template serves `uri`.
+    pub fn contains(&self, uri: &str) -> bool {
+        self.resources.contains_key(uri) || self.match_template(uri).is_some()
+    }
+
+    /// Reads `uri`, or returns `None` if no resource or template serves it.
+    pub async fn read(&self, uri: &str) -> Option<Result<Vec<ResourceContent>>> {
+        if let Some(resource) = self.resources.get(uri) {
+            return Some(resource.read().await);
+        }
+
+        let (template, params) = self.match_template(uri)?;
+        Some(template.read(uri, params).await)
+    }
+
+    fn match_template(&self, uri: &str) -> Option<(&SharedTemplate, HashMap<String, String>)> {
+        // Sorting makes the match deterministic if several templates apply
+        let mut templates: Vec<_> = self.templates.iter().collect();
+        templates.sort_by(|a, b| a.0.cmp(b.0));
+
+        templates
+            .into_iter()
+            .find_map(|(uri_template, template)| {
+                match_uri_template(uri_template, uri).map(|params| (template, params))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_leaves_plain_text_alone() {
+        assert_eq!(percent_decode(""), "");
+        assert_eq!(percent_decode("Japanese::Verbs"), "Japanese::Verbs");
+    }
+
+    #[test]
+    fn percent_decode_decodes_multi_byte_characters() {
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("%E6%97%A5%E6%9C%AC%E8%AA%9E"), "日本語");
+        assert_eq!(percent_decode("%f0%9f%93%9a"), "📚");
+    }
+
+    #[test]
+    fn percent_decode_keeps_incomplete_or_invalid_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%4"), "%4");
+        assert_eq!(percent_decode("%ZZ"), "%ZZ");
+        assert_eq!(percent_decode("%%41"), "%A");
+    }
+
+    #[test]
+    fn percent_decode_replaces_invalid_utf8() {
+        assert_eq!(percent_decode("a%FFb"), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn match_uri_template_extracts_and_decodes_expressions() {
+        let params = match_uri_template("anki://decks/{name}", "anki://decks/Japanese%3A%3AVerbs").unwrap();
+        assert_eq!(params.get("name").map(String::as_str), Some("Japanese::Verbs"));
+    }
+
+    #[test]
+    fn match_uri_template_lets_the_last_expression_take_the_rest() {
+        let params = match_uri_template("anki://decks/{name}", "anki://decks/a/b/c").unwrap();
+        assert_eq!(params.get("name").map(String::as_str), Some("a/b/c"));
+    }
+
+    #[test]
+    fn match_uri_template_splits_on_literals_between_expressions() {
+        let params = match_uri_template("anki://{kind}/{id}/info", "anki://notes/42/info").unwrap();
+        assert_eq!(params.get("kind").map(String::as_str), Some("notes"));
+        assert_eq!(params.get("id").map(String::as_str), Some("42"));
+    }
+
+    #[test]
+    fn match_uri_template_matches_templates_without_expressions_exactly() {
+        assert!(match_uri_template("anki://help", "anki://help").unwrap().is_empty());
+        assert!(match_uri_template("anki://help", "anki://help/more").is_none());
+    }
+
+    #[test]
+    fn match_uri_template_rejects_mismatches() {
+        assert!(match_uri_template("anki://decks/{name}", "anki://notes/1").is_none());
+        assert!(match_uri_template("anki://decks/{name}", "anki://decks/").is_none());
+        assert!(match_uri_template("anki://{kind}/{id}/info", "anki://notes/42").is_none());
+        assert!(match_uri_template("anki://{a}{b}", "anki://xy").is_none());
+        assert!(match_uri_template("anki://decks/{name", "anki://decks/x").is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Result};
+use jsonschema::error::ValidationErrorKind;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use crate::protocol::*;
+
+/// The JSON Schema of a Rust type, for use as a tool's input or output schema.
+pub fn schema_for<T: JsonSchema>() -> serde_json::Value {
+    schemars::schema_for!(T).to_value()
+}
+
+type Validator = Result<jsonschema::Validator, FieldError>;
+
+/// The validator for the schema of `T`, built on first use and kept for the
+/// life of the process, as every tool call checks its arguments against one.
+fn validator_for<T: JsonSchema + 'static>() -> Arc<Validator> {
+    static VALIDATORS: OnceLock<Mutex<HashMap<TypeId, Arc<Validator>>>> = OnceLock::new();
+
+    let mut validators = VALIDATORS.get_or_init(Mutex::default).lock().unwrap();
+    validators
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| Arc::new(compile(&schema_for::<T>())))
+        .clone()
+}
+
+fn compile(schema: &serde_json::Value) -> Validator {
+    jsonschema::validator_for(schema).map_err(|e| FieldError {
+        field: String::new(),
+        message: format!("Invalid schema: {}", e),
+    })
+}
+
+/// A value that violates a schema. `field` is the path to it, e.g. `limit` or
+/// `notes/0/deck`, and empty when the value as a whole is wrong.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.field.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
+
+/// Checks `value` against `schema`, returning every violation. Missing and
+/// unexpected properties are reported against the property itself rather
+/// than the object holding it.
+pub fn validate(schema: &serde_json::Value, value: &serde_json::Value) -> Result<(), Vec<FieldError>> {
+    validate_with(&compile(schema), value)
+}
+
+fn validate_with(validator: &Validator, value: &serde_json::Value) -> Result<(), Vec<FieldError>> {
+    let validator = validator.as_ref().map_err(|e| vec![e.clone()])?;
+
+    let mut errors = Vec::new();
+    for error in validator.iter_errors(value) {
+        let path = error.instance_path().to_string();
+        let path = path.trim_start_matches('/');
+        let child = |name: &str| if path.is_empty() { name.to_string() } else { format!("{}/{}", path, name) };
+
+        match error.kind() {
+            ValidationErrorKind::Required { property } => errors.push(FieldError {
+                field: child(property.as_str().unwrap_or_default()),
+                message: "missing required value".to_string(),
+            }),
+            ValidationErrorKind::AdditionalProperties { unexpected } => {
+                errors.extend(unexpected.iter().map(|name| FieldError {
+                    field: child(name),
+                    message: "unknown property".to_string(),
+                }));
+            }
+            _ => errors.push(FieldError { field: path.to_string(), message: error.to_string() }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Tool arguments that do not match the tool's input schema.
+#[derive(Debug)]
+pub struct InvalidArguments {
+    pub errors: Vec<FieldError>,
+}
+
+impl InvalidArguments {
+    /// A single bad argument, for rules the schema cannot express such as two
+    /// arguments that exclude each other. Tools return it as their error.
+    pub fn field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            errors: vec![FieldError { field: field.into(), message: message.into() }],
+        }
+    }
+
+    /// The `invalid_params` error sent to the client, with each bad field in `data`.
+    pub fn to_mcp_error(&self) -> McpError {
+        McpError {
+            code: -32602,
+            message: self.to_string(),
+            data: Some(serde_json::json!({ "errors": self.errors })),
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidArguments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "Invalid arguments: {}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for InvalidArguments {}
+
+/// Checks tool arguments against the schema of `T` and deserializes them,
+/// reporting every bad field at once.
+pub fn parse_arguments<T: DeserializeOwned + JsonSchema + 'static>(
+    arguments: HashMap<String, serde_json::Value>,
+) -> Result<T, InvalidArguments> {
+    let value = serde_json::Value::Object(arguments.into_iter().collect());
+
+    validate_with(&validator_for::<T>(), &value).map_err(|errors| InvalidArguments { errors })?;
+
+    // Only reachable for constraints the schema cannot express
+    serde_json::from_value(value).map_err(|e| InvalidArguments {
+        errors: vec![FieldError { field: String::new(), message: e.to_string() }],
+    })
+}
+
+/// Builds the successful result of a tool with an output schema: `response` as
+/// structured content, checked against the schema of its type, and the same
+/// data pretty-printed as text.
+pub fn structured_result<T: Serialize + JsonSchema + 'static>(response: &T) -> Result<CallToolResult> {
+    let structured = serde_json::to_value(response)
+        .map_err(|e| anyhow!("Failed to serialize response: {}", e))?;
+
+    validate_with(&validator_for::<T>(), &structured)
+        .map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            anyhow!("Response does not match its output schema: {}", errors.join("; "))
+        })?;
+
+    // Serialized from the response rather than the value to keep its field order
+    let text = serde_json::to_string_pretty(response)
+        .map_err(|e| anyhow!("Failed to serialize response: {}", e))?;
+
+    Ok(CallToolResult {
+        content: vec![ToolResultContent::Text { text }],
+        is_error: Some(false),
+        structured_content: Some(structured),
+    })
+}
@@ -0,0 +1,146 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::protocol::ResourceContent;
+use crate::server::resources::ResourceRegistry;
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The resource subscriptions of every session of a server. A single poller
+/// re-reads each subscribed URI once per interval, however many sessions
+/// subscribed to it, and notifies the sessions that saw it change.
+#[derive(Clone, Default)]
+pub struct Subscriptions {
+    inner: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    /// The sessions subscribed to each URI, with a fingerprint of the contents
+    /// each one last saw (`None` until the resource could be read once).
+    subscribers: HashMap<String, HashMap<u64, Option<u64>>>,
+    /// Where the updated URIs of each running session go.
+    sessions: HashMap<u64, mpsc::UnboundedSender<String>>,
+    polling: bool,
+}
+
+impl Subscriptions {
+    /// Starts delivering updates for `session`, returning where they arrive.
+    pub fn register(&self, session: u64) -> mpsc::UnboundedReceiver<String> {
+        let (updates_tx, updates) = mpsc::unbounded_channel();
+        self.inner.lock().unwrap().sessions.insert(session, updates_tx);
+        updates
+    }
+
+    /// Drops `session` together with all its subscriptions.
+    pub fn unregister(&self, session: u64) {
+        let mut state = self.inner.lock().unwrap();
+        state.sessions.remove(&session);
+        state.subscribers.retain(|_, sessions| {
+            sessions.remove(&session);
+            !sessions.is_empty()
+        });
+    }
+
+    pub fn subscribe(&self, session: u64, uri: String, fingerprint: Option<u64>) {
+        self.inner.lock().unwrap()
+            .subscribers
+            .entry(uri)
+            .or_default()
+            .insert(session, fingerprint);
+    }
+
+    pub fn unsubscribe(&self, session: u64, uri: &str) {
+        let mut state = self.inner.lock().unwrap();
+        if let Some(sessions) = state.subscribers.get_mut(uri) {
+            sessions.remove(&session);
+            if sessions.is_empty() {
+                state.subscribers.remove(uri);
+            }
+        }
+    }
+
+    /// Starts the poller unless it is already running. It stops once every
+    /// clone of these subscriptions is dropped.
+    pub fn start_polling(&self, resources: Arc<ResourceRegistry>, interval: Duration) {
+        let mut state = self.inner.lock().unwrap();
+        if !state.polling {
+            state.polling = true;
+            tokio::spawn(poll_subscriptions(Arc::downgrade(&self.inner), resources, interval));
+        }
+    }
+}
+
+impl State {
+    /// Records the latest fingerprint of `uri` and sends it to the sessions
+    /// that saw different contents. Sessions that unsubscribed while it was
+    /// being read are left alone.
+    fn update(&mut self, uri: &str, fingerprint: u64) {
+        let Some(subscribers) = self.subscribers.get_mut(uri) else {
+            return;
+        };
+
+        for (session, last) in subscribers.iter_mut() {
+            let changed = last.is_some_and(|last| last != fingerprint);
+            *last = Some(fingerprint);
+            if changed {
+                if let Some(updates) = self.sessions.get(session) {
+                    let _ = updates.send(uri.to_string());
+                }
+            }
+        }
+    }
+}
+
+pub fn fingerprint(contents: &[ResourceContent]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(contents).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Re-reads every subscribed resource each `interval` until `state` is dropped.
+async fn poll_subscriptions(state: Weak<Mutex<State>>, resources: Arc<ResourceRegistry>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let uris: Vec<String> = match state.upgrade() {
+            Some(state) => state.lock().unwrap().subscribers.keys().cloned().collect(),
+            None => return,
+        };
+
+        for uri in uris {
+            // Resources talk to AnkiConnect over blocking HTTP, like tools do
+            let runtime = tokio::runtime::Handle::current();
+            let read = {
+                let resources = resources.clone();
+                let uri = uri.clone();
+                tokio::task::spawn_blocking(move || runtime.block_on(resources.read(&uri)))
+            };
+
+            let contents = match read.await {
+                Ok(Some(Ok(contents))) => contents,
+                // Anki may be closed for a while; keep the last fingerprint and retry next time
+                Ok(Some(Err(e))) => {
+                    tracing::debug!("Failed to poll resource '{}': {}", uri, e);
+                    continue;
+                }
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("Polling resource '{}' failed: {}", uri, e);
+                    continue;
+                }
+            };
+
+            let Some(state) = state.upgrade() else {
+                return;
+            };
+            state.lock().unwrap().update(&uri, fingerprint(&contents));
+        }
+    }
+}
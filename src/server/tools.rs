@@ -1,85 +1,75 @@
 use async_trait::async_trait;
-use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Deserialize;
 use crate::protocol::*;
-use crate::server::capabilities::Tool;
+use crate::server::capabilities::{NoArguments, ToolMetadata, TypedTool};
+use crate::server::context::RequestContext;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EchoArgs {
+    /// The text to echo back
+    pub text: String,
+}
 
 pub struct EchoTool;
 
 #[async_trait]
-impl Tool for EchoTool {
-    fn definition(&self, name: String) -> crate::protocol::Tool {
-        crate::protocol::Tool {
-            name,
+impl TypedTool for EchoTool {
+    type Args = EchoArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
             description: Some("Echoes back the provided text".to_string()),
-            input_schema: Some(serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "text": {
-                        "type": "string",
-                        "description": "The text to echo back"
-                    }
-                },
-                "required": ["text"]
-            })),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
         }
     }
 
-    async fn call(&self, arguments: HashMap<String, serde_json::Value>) -> Result<CallToolResult> {
-        let text = arguments.get("text")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow!("Missing or invalid 'text' parameter"))?;
-
+    async fn call(&self, args: EchoArgs, _context: &RequestContext) -> Result<CallToolResult> {
         Ok(CallToolResult {
             content: vec![ToolResultContent::Text {
-                text: format!("Echo: {}", text),
+                text: format!("Echo: {}", args.text),
             }],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AddArgs {
+    /// The first number
+    pub a: f64,
+    /// The second number
+    pub b: f64,
+}
+
 pub struct AddTool;
 
 #[async_trait]
-impl Tool for AddTool {
-    fn definition(&self, name: String) -> crate::protocol::Tool {
-        crate::protocol::Tool {
-            name,
+impl TypedTool for AddTool {
+    type Args = AddArgs;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
             description: Some("Adds two numbers together".to_string()),
-            input_schema: Some(serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "a": {
-                        "type": "number",
-                        "description": "The first number"
-                    },
-                    "b": {
-                        "type": "number",
-                        "description": "The second number"
-                    }
-                },
-                "required": ["a", "b"]
-            })),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
         }
     }
 
-    async fn call(&self, arguments: HashMap<String, serde_json::Value>) -> Result<CallToolResult> {
-        let a = arguments.get("a")
-            .and_then(|v| v.as_f64())
-            .ok_or_else(|| anyhow!("Missing or invalid 'a' parameter"))?;
-
-        let b = arguments.get("b")
-            .and_then(|v| v.as_f64())
-            .ok_or_else(|| anyhow!("Missing or invalid 'b' parameter"))?;
-
-        let result = a + b;
+    async fn call(&self, args: AddArgs, _context: &RequestContext) -> Result<CallToolResult> {
+        let result = args.a + args.b;
 
         Ok(CallToolResult {
             content: vec![ToolResultContent::Text {
-                text: format!("{} + {} = {}", a, b, result),
+                text: format!("{} + {} = {}", args.a, args.b, result),
             }],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
@@ -87,20 +77,18 @@ impl Tool for AddTool {
 pub struct CurrentTimeTool;
 
 #[async_trait]
-impl Tool for CurrentTimeTool {
-    fn definition(&self, name: String) -> crate::protocol::Tool {
-        crate::protocol::Tool {
-            name,
+impl TypedTool for CurrentTimeTool {
+    type Args = NoArguments;
+
+    fn metadata(&self) -> ToolMetadata {
+        ToolMetadata {
             description: Some("Returns the current timestamp".to_string()),
-            input_schema: Some(serde_json::json!({
-                "type": "object",
-                "properties": {},
-                "required": []
-            })),
+            output_schema: None,
+            annotations: Some(ToolAnnotations::read_only()),
         }
     }
 
-    async fn call(&self, _arguments: HashMap<String, serde_json::Value>) -> Result<CallToolResult> {
+    async fn call(&self, _args: NoArguments, _context: &RequestContext) -> Result<CallToolResult> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -111,6 +99,7 @@ impl Tool for CurrentTimeTool {
                 text: format!("Current timestamp: {}", now),
             }],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
\ No newline at end of file
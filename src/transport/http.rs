@@ -0,0 +1,459 @@
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use crate::protocol::*;
+use crate::transport::{Listener, Transport};
+
+pub const MCP_ENDPOINT: &str = "/mcp";
+pub const SESSION_HEADER: &str = "mcp-session-id";
+pub const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+/// Sessions without open streams or running requests are closed after this long.
+pub const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const SESSION_REAP_INTERVAL: Duration = Duration::from_secs(60);
+
+type MessageSender = mpsc::UnboundedSender<JsonRpcMessage>;
+
+/// Where a session's outgoing messages go: responses, and progress or a
+/// cancelled request's partial result, to the stream of the POST that sent the
+/// request; everything else to the GET stream if one is open.
+#[derive(Default)]
+struct Routes {
+    pending: HashMap<String, MessageSender>,
+    /// Progress token of a pending request -> that request's key
+    progress: HashMap<String, String>,
+    standalone: Option<MessageSender>,
+}
+
+impl Routes {
+    /// Forgets a request that is answered or dropped, returning its stream.
+    fn remove_request(&mut self, key: &str) -> Option<MessageSender> {
+        self.progress.retain(|_, request| request != key);
+        self.pending.remove(key)
+    }
+
+    fn is_idle(&self) -> bool {
+        self.pending.is_empty() && self.standalone.as_ref().is_none_or(|stream| stream.is_closed())
+    }
+}
+
+struct Session {
+    incoming: MessageSender,
+    routes: Arc<Mutex<Routes>>,
+    last_active: Instant,
+}
+
+struct HttpState {
+    sessions: Mutex<HashMap<String, Session>>,
+    new_sessions: mpsc::UnboundedSender<HttpTransport>,
+}
+
+impl HttpState {
+    fn create_session(self: &Arc<Self>) -> Result<String> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let (incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let routes = Arc::new(Mutex::new(Routes::default()));
+
+        self.sessions.lock().unwrap().insert(session_id.clone(), Session {
+            incoming: incoming_tx,
+            routes: routes.clone(),
+            last_active: Instant::now(),
+        });
+
+        self.new_sessions
+            .send(HttpTransport {
+                session_id: session_id.clone(),
+                incoming: incoming_rx,
+                routes,
+                state: self.clone(),
+            })
+            .map_err(|_| anyhow!("Server is not accepting new sessions"))?;
+
+        tracing::info!("Created HTTP session {}", session_id);
+        Ok(session_id)
+    }
+
+    /// Closes sessions that have had no requests and no open stream for
+    /// `SESSION_IDLE_TIMEOUT`, so abandoned sessions don't live forever.
+    fn close_idle_sessions(&self) {
+        self.sessions.lock().unwrap().retain(|session_id, session| {
+            // The idle time counts from when the last request finished or stream closed
+            if !session.routes.lock().unwrap().is_idle() {
+                session.last_active = Instant::now();
+                return true;
+            }
+
+            let expired = session.last_active.elapsed() >= SESSION_IDLE_TIMEOUT;
+            if expired {
+                tracing::info!("Closing HTTP session {} after {:?} without activity", session_id, SESSION_IDLE_TIMEOUT);
+            }
+            !expired
+        });
+    }
+}
+
+/// Streamable HTTP transport: clients POST JSON-RPC messages to a single
+/// endpoint and receive responses as JSON or as an SSE stream.
+pub struct HttpListener {
+    new_sessions: mpsc::UnboundedReceiver<HttpTransport>,
+    local_addr: SocketAddr,
+}
+
+impl HttpListener {
+    pub async fn bind(addr: SocketAddr) -> Result<Self> {
+        let listener = tokio::net::TcpListener::bind(addr).await
+            .map_err(|e| anyhow!("Failed to listen on {}: {}", addr, e))?;
+        let local_addr = listener.local_addr()?;
+
+        let (new_sessions_tx, new_sessions) = mpsc::unbounded_channel();
+        let state = Arc::new(HttpState {
+            sessions: Mutex::new(HashMap::new()),
+            new_sessions: new_sessions_tx,
+        });
+
+        let app = Router::new()
+            .route(MCP_ENDPOINT, post(handle_post).get(handle_get).delete(handle_delete))
+            .with_state(state.clone());
+
+        // Dropping a session's sender makes its transport report the session as closed
+        let reaped = Arc::downgrade(&state);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SESSION_REAP_INTERVAL);
+            loop {
+                interval.tick().await;
+                match reaped.upgrade() {
+                    Some(state) => state.close_idle_sessions(),
+                    None => break,
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("HTTP server failed: {}", e);
+            }
+        });
+
+        tracing::info!("Streamable HTTP transport listening on http://{}{}", local_addr, MCP_ENDPOINT);
+
+        Ok(Self { new_sessions, local_addr })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+#[async_trait]
+impl Listener for HttpListener {
+    type Transport = HttpTransport;
+
+    async fn accept(&mut self) -> Result<HttpTransport> {
+        self.new_sessions.recv().await
+            .ok_or_else(|| anyhow!("HTTP server stopped"))
+    }
+}
+
+/// One client session of the Streamable HTTP transport.
+pub struct HttpTransport {
+    session_id: String,
+    incoming: mpsc::UnboundedReceiver<JsonRpcMessage>,
+    routes: Arc<Mutex<Routes>>,
+    state: Arc<HttpState>,
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn read_message(&mut self) -> Result<JsonRpcMessage> {
+        self.incoming.recv().await
+            .ok_or_else(|| anyhow!("HTTP session {} closed", self.session_id))
+    }
+
+    async fn write_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        let target = {
+            let mut routes = self.routes.lock().unwrap();
+            match &message.content {
+                MessageContent::Response(response) => routes.remove_request(&request_key(&response.id)),
+                MessageContent::Notification(notification) if notification.method == "notifications/progress" => notification.params
+                    .as_ref()
+                    .and_then(|params| params.get("progressToken"))
+                    .and_then(|token| routes.progress.get(&token.to_string()))
+                    .and_then(|request| routes.pending.get(request))
+                    .cloned(),
+                MessageContent::Notification(notification) if notification.method == "notifications/message" => notification.params
+                    .as_ref()
+                    .and_then(|params| params.pointer("/data/requestId"))
+                    .and_then(|id| routes.pending.get(&request_key(id)))
+                    .or(routes.standalone.as_ref())
+                    .cloned()
+                    .filter(|stream| !stream.is_closed()),
+                _ => routes.standalone
+                    .clone()
+                    .filter(|stream| !stream.is_closed()),
+            }
+        };
+
+        match target {
+            Some(stream) => {
+                if stream.send(message).is_err() {
+                    tracing::debug!("Client of HTTP session {} closed its stream", self.session_id);
+                }
+            }
+            None => tracing::debug!("No open stream in HTTP session {}, dropping message", self.session_id),
+        }
+
+        Ok(())
+    }
+
+    fn request_dropped(&mut self, id: &serde_json::Value) {
+        // Closes the POST's stream once none of its requests is left
+        self.routes.lock().unwrap().remove_request(&request_key(id));
+    }
+}
+
+impl Drop for HttpTransport {
+    fn drop(&mut self) {
+        self.state.sessions.lock().unwrap().remove(&self.session_id);
+        tracing::info!("Closed HTTP session {}", self.session_id);
+    }
+}
+
+fn request_key(id: &serde_json::Value) -> String {
+    id.to_string()
+}
+
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers.get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+fn accepts(headers: &HeaderMap, mime_type: &str) -> bool {
+    headers.get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|part| part.trim().starts_with(mime_type)))
+}
+
+/// Whether the Origin header is acceptable: browser requests from non-local
+/// pages are refused to prevent DNS rebinding attacks.
+pub(crate) fn origin_allowed(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) else {
+        return true;
+    };
+
+    let host = origin
+        .split_once("://")
+        .map_or(origin, |(_, rest)| rest)
+        .trim_end_matches('/');
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+
+    let allowed = matches!(host, "localhost" | "127.0.0.1" | "::1");
+    if !allowed {
+        tracing::warn!("Rejected request from origin {}", origin);
+    }
+    allowed
+}
+
+/// Clients on 2025-06-18 send the negotiated version with every request after
+/// `initialize`; a version we don't speak is refused. Older clients send none.
+fn protocol_version_supported(headers: &HeaderMap) -> bool {
+    match headers.get(PROTOCOL_VERSION_HEADER) {
+        Some(value) => value.to_str().ok().and_then(ProtocolVersion::parse).is_some(),
+        None => true,
+    }
+}
+
+pub(crate) fn error_response(status: StatusCode, error: McpError) -> Response {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": error,
+    });
+    (status, Json(body)).into_response()
+}
+
+/// Parses a POST body holding a single message or a batch of messages.
+fn parse_body(body: &str) -> Result<(Vec<JsonRpcMessage>, bool)> {
+    if body.trim_start().starts_with('[') {
+        let messages: Vec<JsonRpcMessage> = serde_json::from_str(body)?;
+        Ok((messages, true))
+    } else {
+        Ok((vec![serde_json::from_str(body)?], false))
+    }
+}
+
+async fn handle_post(State(state): State<Arc<HttpState>>, headers: HeaderMap, body: String) -> Response {
+    if !origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    if !protocol_version_supported(&headers) {
+        return error_response(StatusCode::BAD_REQUEST, McpError::custom(-32600, "Unsupported MCP-Protocol-Version".to_string()));
+    }
+
+    let (messages, batch) = match parse_body(&body) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::debug!("Failed to parse JSON-RPC message: {}", e);
+            return error_response(StatusCode::BAD_REQUEST, McpError::parse_error());
+        }
+    };
+
+    let is_initialize = messages.iter().any(|m| {
+        matches!(&m.content, MessageContent::Request(request) if request.method == "initialize")
+    });
+
+    let (session_id, created) = match session_id(&headers) {
+        Some(session_id) => (session_id, false),
+        None if is_initialize => match state.create_session() {
+            Ok(session_id) => (session_id, true),
+            Err(e) => return error_response(StatusCode::SERVICE_UNAVAILABLE, McpError::custom(-32603, e.to_string())),
+        },
+        None => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                McpError::custom(-32600, "Missing Mcp-Session-Id header; send initialize first".to_string()),
+            );
+        }
+    };
+
+    let requests: Vec<(String, Option<serde_json::Value>)> = messages
+        .iter()
+        .filter_map(|m| match &m.content {
+            MessageContent::Request(request) => Some((
+                request_key(&request.id),
+                RequestMeta::from_params(&request.params).progress_token,
+            )),
+            _ => None,
+        })
+        .collect();
+
+    let (stream_tx, mut stream_rx) = mpsc::unbounded_channel();
+    {
+        let mut sessions = state.sessions.lock().unwrap();
+        let Some(session) = sessions.get_mut(&session_id) else {
+            return error_response(StatusCode::NOT_FOUND, McpError::custom(-32001, "Session not found".to_string()));
+        };
+        session.last_active = Instant::now();
+
+        let mut routes = session.routes.lock().unwrap();
+        for (id, progress_token) in &requests {
+            routes.pending.insert(id.clone(), stream_tx.clone());
+            if let Some(token) = progress_token {
+                routes.progress.insert(token.to_string(), id.clone());
+            }
+        }
+        drop(routes);
+
+        for message in messages {
+            if session.incoming.send(message).is_err() {
+                return error_response(StatusCode::NOT_FOUND, McpError::custom(-32001, "Session not found".to_string()));
+            }
+        }
+    }
+    // The stream ends once every request of this POST has been answered and its route removed
+    drop(stream_tx);
+
+    if requests.is_empty() {
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    let mut response = if accepts(&headers, "text/event-stream") {
+        let events = UnboundedReceiverStream::new(stream_rx)
+            .map(|message| Event::default().event("message").json_data(message));
+        Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+    } else {
+        let mut responses = Vec::new();
+        while let Some(message) = stream_rx.recv().await {
+            if matches!(message.content, MessageContent::Response(_)) {
+                responses.push(message);
+            }
+        }
+
+        if responses.is_empty() {
+            // Every request was cancelled, unless the session went away
+            if state.sessions.lock().unwrap().contains_key(&session_id) {
+                return StatusCode::ACCEPTED.into_response();
+            }
+            return error_response(StatusCode::NOT_FOUND, McpError::custom(-32001, "Session closed".to_string()));
+        }
+
+        if batch {
+            Json(responses).into_response()
+        } else {
+            Json(responses.remove(0)).into_response()
+        }
+    };
+
+    if created {
+        if let Ok(value) = HeaderValue::from_str(&session_id) {
+            response.headers_mut().insert(SESSION_HEADER, value);
+        }
+    }
+
+    response
+}
+
+/// Opens a stream for server messages that are not responses to a POST.
+async fn handle_get(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    if !protocol_version_supported(&headers) {
+        return error_response(StatusCode::BAD_REQUEST, McpError::custom(-32600, "Unsupported MCP-Protocol-Version".to_string()));
+    }
+
+    if !accepts(&headers, "text/event-stream") {
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    }
+
+    let Some(session_id) = session_id(&headers) else {
+        return error_response(StatusCode::BAD_REQUEST, McpError::custom(-32600, "Missing Mcp-Session-Id header".to_string()));
+    };
+
+    let (stream_tx, stream_rx) = mpsc::unbounded_channel();
+    {
+        let mut sessions = state.sessions.lock().unwrap();
+        let Some(session) = sessions.get_mut(&session_id) else {
+            return error_response(StatusCode::NOT_FOUND, McpError::custom(-32001, "Session not found".to_string()));
+        };
+        session.last_active = Instant::now();
+        session.routes.lock().unwrap().standalone = Some(stream_tx);
+    }
+
+    let events = UnboundedReceiverStream::new(stream_rx)
+        .map(|message| Event::default().event("message").json_data(message));
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Ends a session at the client's request.
+async fn handle_delete(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    let Some(session_id) = session_id(&headers) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    // Dropping the session's sender makes its transport report the session as closed
+    match state.sessions.lock().unwrap().remove(&session_id) {
+        Some(_) => StatusCode::NO_CONTENT.into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use crate::protocol::JsonRpcMessage;
+use crate::transport::Transport;
+
+/// Newline-delimited JSON-RPC over a byte stream: one message per line.
+pub struct LineTransport<R, W> {
+    reader: BufReader<R>,
+    writer: W,
+    // Bytes of a partially read line. `read_until` only appends to it and is
+    // cancel safe, so a read dropped by the server's `select!` resumes here
+    line: Vec<u8>,
+}
+
+impl<R, W> LineTransport<R, W>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            writer,
+            line: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl<R, W> Transport for LineTransport<R, W>
+where
+    R: AsyncRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    async fn read_message(&mut self) -> Result<JsonRpcMessage> {
+        let read = self.reader.read_until(b'\n', &mut self.line).await
+            .map_err(|e| anyhow!("Failed to read message: {}", e))?;
+
+        // A last line without a newline is still handled, as it was complete
+        if read == 0 && self.line.is_empty() {
+            return Err(anyhow!("EOF reached"));
+        }
+
+        let line = std::mem::take(&mut self.line);
+        let line = std::str::from_utf8(&line)
+            .map_err(|e| anyhow!("Received a line that is not valid UTF-8: {}", e))?
+            .trim();
+        if line.is_empty() {
+            return Err(anyhow!("Empty line received"));
+        }
+
+        tracing::debug!("Received: {}", line);
+
+        let message: JsonRpcMessage = serde_json::from_str(line)
+            .map_err(|e| anyhow!("Failed to parse JSON-RPC message: {}", e))?;
+
+        Ok(message)
+    }
+
+    async fn write_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        let mut json = serde_json::to_string(&message)
+            .map_err(|e| anyhow!("Failed to serialize JSON-RPC message: {}", e))?;
+
+        tracing::debug!("Sending: {}", json);
+
+        json.push('\n');
+        self.writer.write_all(json.as_bytes()).await
+            .map_err(|e| anyhow!("Failed to write message: {}", e))?;
+
+        self.writer.flush().await
+            .map_err(|e| anyhow!("Failed to flush message: {}", e))?;
+
+        Ok(())
+    }
+}
@@ -1,11 +1,50 @@
+pub mod lines;
 pub mod stdio;
+pub mod http;
+pub mod sse;
+pub mod websocket;
+#[cfg(unix)]
+pub mod unix;
 
 use async_trait::async_trait;
 use anyhow::Result;
 use crate::protocol::JsonRpcMessage;
 
+/// A bidirectional JSON-RPC message channel.
+///
+/// `read_message` must be cancel safe: the server races it against outgoing
+/// notifications and drops the pending read when a notification is sent first.
 #[async_trait]
-pub trait Transport {
+pub trait Transport: Send {
     async fn read_message(&mut self) -> Result<JsonRpcMessage>;
     async fn write_message(&mut self, message: JsonRpcMessage) -> Result<()>;
-}
\ No newline at end of file
+
+    /// Called when a request was cancelled and will get no response, for
+    /// transports that hold state until a request is answered.
+    fn request_dropped(&mut self, _id: &serde_json::Value) {}
+
+    /// Called once the session ends, after its last message was written, so
+    /// the transport can tell the client why it is closed.
+    async fn close(&mut self) {}
+}
+
+/// The error `read_message` returns for a message that is not valid JSON-RPC.
+/// The server answers it with a parse error and ends the session.
+#[derive(Debug)]
+pub struct InvalidMessage(pub String);
+
+impl std::fmt::Display for InvalidMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to parse JSON-RPC message: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidMessage {}
+
+/// Accepts clients of a network transport, one `Transport` per client session.
+#[async_trait]
+pub trait Listener {
+    type Transport: Transport + Send + 'static;
+
+    async fn accept(&mut self) -> Result<Self::Transport>;
+}
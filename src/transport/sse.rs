@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use crate::protocol::*;
+use crate::transport::http::{error_response, origin_allowed};
+use crate::transport::{Listener, Transport};
+
+pub const SSE_ENDPOINT: &str = "/sse";
+pub const MESSAGES_ENDPOINT: &str = "/messages";
+
+struct SseState {
+    sessions: Mutex<HashMap<String, mpsc::UnboundedSender<JsonRpcMessage>>>,
+    new_sessions: mpsc::UnboundedSender<SseTransport>,
+}
+
+/// The HTTP+SSE transport of MCP 2024-11-05: clients open an SSE stream with
+/// GET /sse, learn their message endpoint from its first event and POST
+/// messages there; every server message arrives on the stream.
+pub struct SseListener {
+    new_sessions: mpsc::UnboundedReceiver<SseTransport>,
+    local_addr: SocketAddr,
+}
+
+impl SseListener {
+    pub async fn bind(addr: SocketAddr) -> Result<Self> {
+        let listener = tokio::net::TcpListener::bind(addr).await
+            .map_err(|e| anyhow!("Failed to listen on {}: {}", addr, e))?;
+        let local_addr = listener.local_addr()?;
+
+        let (new_sessions_tx, new_sessions) = mpsc::unbounded_channel();
+        let state = Arc::new(SseState {
+            sessions: Mutex::new(HashMap::new()),
+            new_sessions: new_sessions_tx,
+        });
+
+        let app = Router::new()
+            .route(SSE_ENDPOINT, get(handle_sse))
+            .route(MESSAGES_ENDPOINT, post(handle_message))
+            .with_state(state);
+
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("HTTP server failed: {}", e);
+            }
+        });
+
+        tracing::info!("HTTP+SSE transport listening on http://{}{}", local_addr, SSE_ENDPOINT);
+
+        Ok(Self { new_sessions, local_addr })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+#[async_trait]
+impl Listener for SseListener {
+    type Transport = SseTransport;
+
+    async fn accept(&mut self) -> Result<SseTransport> {
+        self.new_sessions.recv().await
+            .ok_or_else(|| anyhow!("HTTP server stopped"))
+    }
+}
+
+/// One client session of the HTTP+SSE transport.
+pub struct SseTransport {
+    session_id: String,
+    incoming: mpsc::UnboundedReceiver<JsonRpcMessage>,
+    outgoing: mpsc::UnboundedSender<JsonRpcMessage>,
+    state: Arc<SseState>,
+}
+
+#[async_trait]
+impl Transport for SseTransport {
+    async fn read_message(&mut self) -> Result<JsonRpcMessage> {
+        tokio::select! {
+            message = self.incoming.recv() => message
+                .ok_or_else(|| anyhow!("SSE session {} closed", self.session_id)),
+            // The stream is dropped when the client disconnects, even if it never POSTs again
+            _ = self.outgoing.closed() => Err(anyhow!("SSE client of session {} disconnected", self.session_id)),
+        }
+    }
+
+    async fn write_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        self.outgoing.send(message)
+            .map_err(|_| anyhow!("SSE client of session {} disconnected", self.session_id))
+    }
+}
+
+impl Drop for SseTransport {
+    fn drop(&mut self) {
+        self.state.sessions.lock().unwrap().remove(&self.session_id);
+        tracing::info!("Closed SSE session {}", self.session_id);
+    }
+}
+
+async fn handle_sse(State(state): State<Arc<SseState>>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let (incoming_tx, incoming) = mpsc::unbounded_channel();
+    let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+
+    state.sessions.lock().unwrap().insert(session_id.clone(), incoming_tx);
+
+    let transport = SseTransport {
+        session_id: session_id.clone(),
+        incoming,
+        outgoing,
+        state: state.clone(),
+    };
+    if state.new_sessions.send(transport).is_err() {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+
+    tracing::info!("Created SSE session {}", session_id);
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("{}?sessionId={}", MESSAGES_ENDPOINT, session_id));
+    let events = tokio_stream::once(Ok(endpoint)).chain(
+        UnboundedReceiverStream::new(outgoing_rx)
+            .map(|message| Event::default().event("message").json_data(message)),
+    );
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+#[derive(Deserialize)]
+struct MessageQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+async fn handle_message(
+    State(state): State<Arc<SseState>>,
+    Query(query): Query<MessageQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if !origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    let message: JsonRpcMessage = match serde_json::from_str(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            tracing::debug!("Failed to parse JSON-RPC message: {}", e);
+            return error_response(StatusCode::BAD_REQUEST, McpError::parse_error());
+        }
+    };
+
+    let sessions = state.sessions.lock().unwrap();
+    match sessions.get(&query.session_id) {
+        // The reply, if any, is sent on the session's SSE stream
+        Some(incoming) if incoming.send(message).is_ok() => (StatusCode::ACCEPTED, "Accepted").into_response(),
+        _ => error_response(StatusCode::NOT_FOUND, McpError::custom(-32001, "Session not found".to_string())),
+    }
+}
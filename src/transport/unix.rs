@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use crate::protocol::JsonRpcMessage;
+use crate::transport::lines::LineTransport;
+use crate::transport::{Listener, Transport};
+
+/// Unix domain socket transport: local clients connect to a socket file and
+/// exchange newline-delimited JSON-RPC messages, exactly as over stdio.
+pub struct UnixSocketListener {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl UnixSocketListener {
+    pub async fn bind(path: &Path) -> Result<Self> {
+        if path.exists() {
+            // A socket file nobody listens on is left over from a previous run
+            if UnixStream::connect(path).await.is_ok() {
+                return Err(anyhow!("Another server is already listening on {}", path.display()));
+            }
+            std::fs::remove_file(path)
+                .map_err(|e| anyhow!("Failed to remove stale socket {}: {}", path.display(), e))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .map_err(|e| anyhow!("Failed to listen on {}: {}", path.display(), e))?;
+
+        // Only the current user may talk to their Anki collection
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| anyhow!("Failed to restrict permissions of {}: {}", path.display(), e))?;
+
+        tracing::info!("Unix socket transport listening on {}", path.display());
+
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for UnixSocketListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[async_trait]
+impl Listener for UnixSocketListener {
+    type Transport = UnixSocketTransport;
+
+    async fn accept(&mut self) -> Result<UnixSocketTransport> {
+        let (stream, _) = self.listener.accept().await
+            .map_err(|e| anyhow!("Failed to accept connection on {}: {}", self.path.display(), e))?;
+
+        tracing::info!("Client connected to {}", self.path.display());
+
+        let (reader, writer) = stream.into_split();
+        Ok(UnixSocketTransport(LineTransport::new(reader, writer)))
+    }
+}
+
+/// One client connection of the Unix socket transport.
+pub struct UnixSocketTransport(LineTransport<OwnedReadHalf, OwnedWriteHalf>);
+
+#[async_trait]
+impl Transport for UnixSocketTransport {
+    async fn read_message(&mut self) -> Result<JsonRpcMessage> {
+        self.0.read_message().await
+    }
+
+    async fn write_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        self.0.write_message(message).await
+    }
+}
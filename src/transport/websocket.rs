@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use anyhow::{anyhow, Result};
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use crate::protocol::*;
+use crate::transport::http::origin_allowed;
+use crate::transport::{InvalidMessage, Listener, Transport};
+
+pub const WEBSOCKET_ENDPOINT: &str = "/ws";
+
+/// WebSocket transport: every client connection is its own session, and each
+/// JSON-RPC message travels as one text message.
+pub struct WebSocketListener {
+    new_sessions: mpsc::UnboundedReceiver<WebSocketTransport>,
+    local_addr: SocketAddr,
+}
+
+impl WebSocketListener {
+    pub async fn bind(addr: SocketAddr) -> Result<Self> {
+        let listener = tokio::net::TcpListener::bind(addr).await
+            .map_err(|e| anyhow!("Failed to listen on {}: {}", addr, e))?;
+        let local_addr = listener.local_addr()?;
+
+        let (new_sessions_tx, new_sessions) = mpsc::unbounded_channel();
+
+        let app = Router::new()
+            .route(WEBSOCKET_ENDPOINT, get(handle_upgrade))
+            .with_state(Arc::new(new_sessions_tx));
+
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("HTTP server failed: {}", e);
+            }
+        });
+
+        tracing::info!("WebSocket transport listening on ws://{}{}", local_addr, WEBSOCKET_ENDPOINT);
+
+        Ok(Self { new_sessions, local_addr })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+#[async_trait]
+impl Listener for WebSocketListener {
+    type Transport = WebSocketTransport;
+
+    async fn accept(&mut self) -> Result<WebSocketTransport> {
+        self.new_sessions.recv().await
+            .ok_or_else(|| anyhow!("HTTP server stopped"))
+    }
+}
+
+/// One WebSocket client connection.
+pub struct WebSocketTransport {
+    socket: WebSocket,
+    /// Why the connection is closed, sent once the session has ended. Set by
+    /// `read_message`, which must not write as the server may drop it midway.
+    close_frame: Option<CloseFrame>,
+}
+
+impl WebSocketTransport {
+    fn closing(&mut self, code: u16, reason: &'static str) {
+        self.close_frame = Some(CloseFrame {
+            code,
+            reason: reason.into(),
+        });
+    }
+}
+
+#[async_trait]
+impl Transport for WebSocketTransport {
+    async fn read_message(&mut self) -> Result<JsonRpcMessage> {
+        loop {
+            let message = match self.socket.recv().await {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => return Err(anyhow!("Failed to read from WebSocket: {}", e)),
+                None => return Err(anyhow!("WebSocket closed")),
+            };
+
+            match message {
+                Message::Text(text) => {
+                    tracing::debug!("Received: {}", text.as_str());
+
+                    return serde_json::from_str(text.as_str()).map_err(|e| {
+                        self.closing(close_code::INVALID, "Invalid JSON-RPC message");
+                        InvalidMessage(e.to_string()).into()
+                    });
+                }
+                Message::Binary(_) => {
+                    self.closing(close_code::UNSUPPORTED, "Only text messages are supported");
+                    return Err(anyhow!("Received binary WebSocket message"));
+                }
+                Message::Close(_) => return Err(anyhow!("WebSocket closed by client")),
+                // Pings are answered by the WebSocket implementation itself
+                Message::Ping(_) | Message::Pong(_) => {}
+            }
+        }
+    }
+
+    async fn write_message(&mut self, message: JsonRpcMessage) -> Result<()> {
+        let json = serde_json::to_string(&message)
+            .map_err(|e| anyhow!("Failed to serialize JSON-RPC message: {}", e))?;
+
+        tracing::debug!("Sending: {}", json);
+
+        self.socket.send(Message::text(json)).await
+            .map_err(|e| anyhow!("Failed to write to WebSocket: {}", e))
+    }
+
+    async fn close(&mut self) {
+        if let Some(frame) = self.close_frame.take() {
+            if let Err(e) = self.socket.send(Message::Close(Some(frame))).await {
+                tracing::debug!("Failed to send WebSocket close frame: {}", e);
+            }
+        }
+    }
+}
+
+async fn handle_upgrade(
+    State(new_sessions): State<Arc<mpsc::UnboundedSender<WebSocketTransport>>>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    if !origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+
+    upgrade
+        .protocols(["mcp"])
+        .on_upgrade(move |socket| async move {
+            tracing::info!("WebSocket client connected");
+            if new_sessions.send(WebSocketTransport { socket, close_frame: None }).is_err() {
+                tracing::warn!("Server is not accepting new sessions");
+            }
+        })
+}
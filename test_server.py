@@ -51,6 +51,7 @@ def test_mcp_server():
         response = send_message(init_request)
         assert response is not None, "No response to initialize"
         assert response.get("result"), "Initialize failed"
+        assert response["result"]["protocolVersion"] == "2024-11-05", "Server did not accept 2024-11-05"
         print("✓ Initialize test passed")
         
         # Send initialized notification (no response expected)
@@ -75,7 +76,38 @@ def test_mcp_server():
         assert response is not None, "No response to tools/list"
         assert "result" in response, "tools/list failed"
         tools = response["result"]["tools"]
-        assert len(tools) == 1, f"Expected 1 tool, got {len(tools)}"
+        tool_names = {tool["name"] for tool in tools}
+        expected_tools = {
+            "list_decks",
+            "create_deck",
+            "rename_deck",
+            "delete_deck",
+            "list_note_types",
+            "get_note_type",
+            "create_note_type",
+            "edit_note_type_fields",
+            "update_note_type_templates",
+            "update_note_type_styling",
+            "find_notes",
+            "add_note",
+            "add_notes",
+            "get_cards",
+            "review_stats",
+            "suspend_cards",
+            "unsuspend_cards",
+            "bury_cards",
+            "forget_cards",
+            "set_due_date",
+            "store_media_file",
+            "retrieve_media_file",
+            "list_media_files",
+            "delete_unused_media",
+            "update_note_fields",
+            "update_note_tags",
+            "delete_notes",
+        }
+        assert expected_tools <= tool_names, f"Unexpected tools: {tool_names}"
+        assert all("annotations" not in tool for tool in tools), "Tool annotations sent to a 2024-11-05 client"
         print("✓ List tools test passed")
         
         # Test 3: Call list_decks tool
@@ -144,6 +176,87 @@ def test_mcp_server():
         assert "AnkiConnect MCP Server" in content, f"Unexpected about resource content: {content}"
         print("✓ Read about resource test passed")
         
+        # Test 7: List resource templates
+        list_templates_request = {
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "resources/templates/list"
+        }
+        
+        response = send_message(list_templates_request)
+        assert response is not None, "No response to resources/templates/list"
+        assert "result" in response, "resources/templates/list failed"
+        templates = {t["uriTemplate"] for t in response["result"]["resourceTemplates"]}
+        assert templates == {"anki://deck/{name}", "anki://note/{id}"}, f"Unexpected templates: {templates}"
+        print("✓ List resource templates test passed")
+        
+        # Test 8: Subscribe to and unsubscribe from a resource
+        for request_id, method in [(8, "resources/subscribe"), (9, "resources/unsubscribe")]:
+            response = send_message({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "method": method,
+                "params": {"uri": "anki://about"}
+            })
+            assert response is not None, f"No response to {method}"
+            assert response.get("result") == {}, f"{method} failed: {response}"
+        print("✓ Resource subscription test passed")
+        
+        # Test 9: List prompts
+        response = send_message({
+            "jsonrpc": "2.0",
+            "id": 10,
+            "method": "prompts/list"
+        })
+        assert response is not None, "No response to prompts/list"
+        assert "result" in response, "prompts/list failed"
+        prompt_names = {p["name"] for p in response["result"]["prompts"]}
+        expected_prompts = {"make_cloze_cards", "review_leeches", "summarize_study_progress"}
+        assert prompt_names == expected_prompts, f"Unexpected prompts: {prompt_names}"
+        print("✓ List prompts test passed")
+        
+        # Test 10: Getting a prompt without its required argument fails
+        response = send_message({
+            "jsonrpc": "2.0",
+            "id": 11,
+            "method": "prompts/get",
+            "params": {"name": "make_cloze_cards", "arguments": {}}
+        })
+        assert response is not None, "No response to prompts/get"
+        assert response.get("error", {}).get("code") == -32602, f"Expected invalid params error: {response}"
+        print("✓ Get prompt argument validation test passed")
+        
+        # Test 11: Unknown protocol versions are answered with the latest one,
+        # which enables tool annotations and output schemas
+        init_request["id"] = 12
+        init_request["params"]["protocolVersion"] = "1999-01-01"
+        response = send_message(init_request)
+        assert response is not None, "No response to initialize"
+        assert response["result"]["protocolVersion"] == "2025-06-18", f"Unexpected version: {response}"
+        
+        response = send_message({"jsonrpc": "2.0", "id": 13, "method": "tools/list"})
+        assert response is not None, "No response to tools/list"
+        annotations = {tool["name"]: tool.get("annotations") for tool in response["result"]["tools"]}
+        assert annotations["list_decks"]["readOnlyHint"] is True, f"Missing annotations: {annotations['list_decks']}"
+        assert annotations["delete_deck"]["destructiveHint"] is True, f"Missing annotations: {annotations['delete_deck']}"
+        output_schemas = {tool["name"]: tool.get("outputSchema") for tool in response["result"]["tools"]}
+        assert output_schemas["list_decks"]["type"] == "object", "list_decks has no output schema"
+        print("✓ Protocol version negotiation test passed")
+        
+        # Test 12: Typed tools reject bad arguments before running, naming each bad field
+        response = send_message({
+            "jsonrpc": "2.0",
+            "id": 14,
+            "method": "tools/call",
+            "params": {"name": "find_notes", "arguments": {"limit": 0}}
+        })
+        assert response is not None, "No response to tools/call"
+        error = response.get("error", {})
+        assert error.get("code") == -32602, f"Expected invalid params error: {response}"
+        bad_fields = {e["field"] for e in error["data"]["errors"]}
+        assert bad_fields == {"query", "limit"}, f"Unexpected bad fields: {bad_fields}"
+        print("✓ Tool argument validation test passed")
+        
         print("\n🎉 All tests passed! MCP server is working correctly.")
         
     except Exception as e:
@@ -0,0 +1,195 @@
+//! Runs the Anki tools against a mock AnkiConnect that knows notes 1 and 2,
+//! with one card each, and a card 30 it returns in a shape the server does
+//! not expect. Media files are stored under the name they are given.
+
+use ankiconnect_mcp::protocol::{CallToolResult, ToolResultContent};
+use ankiconnect_mcp::server::anki_connect;
+use ankiconnect_mcp::server::anki_tools::{
+    DeleteDeckTool, DeleteNotesTool, GetCardsTool, MediaPathAccess, ReviewStatsTool, StoreMediaFileTool,
+    UpdateNoteTagsTool,
+};
+use ankiconnect_mcp::server::capabilities::Tool;
+use ankiconnect_mcp::server::schema::InvalidArguments;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Once;
+
+fn note(id: u64) -> Value {
+    json!({
+        "noteId": id,
+        "modelName": "Basic",
+        "tags": [],
+        "fields": {
+            "Front": { "value": format!("front {}", id), "order": 0 },
+            "Back": { "value": format!("back {}", id), "order": 1 },
+        },
+        "cards": [id * 10],
+    })
+}
+
+fn card(id: u64) -> Value {
+    match id {
+        10 | 20 => json!({ "cardId": id, "note": id / 10, "deckName": "Default", "reps": 3 }),
+        30 => json!({ "cardId": id, "note": 3, "deckName": 1 }),
+        _ => json!({}),
+    }
+}
+
+fn ids(params: &Value, key: &str) -> Vec<u64> {
+    params[key].as_array().into_iter().flatten().filter_map(Value::as_u64).collect()
+}
+
+async fn handle(Json(request): Json<Value>) -> Json<Value> {
+    let params = &request["params"];
+    let result = match request["action"].as_str().unwrap_or_default() {
+        // Unknown notes come back as empty objects, like AnkiConnect does
+        "notesInfo" => ids(params, "notes")
+            .into_iter()
+            .map(|id| if (1..=2).contains(&id) { note(id) } else { json!({}) })
+            .collect(),
+        "cardsInfo" => ids(params, "cards").into_iter().map(card).collect(),
+        "deleteNotes" => Value::Null,
+        "storeMediaFile" => params["filename"].clone(),
+        action => return Json(json!({ "result": null, "error": format!("unsupported action {}", action) })),
+    };
+    Json(json!({ "result": result, "error": null }))
+}
+
+/// Starts the mock on its own runtime, as the tools call AnkiConnect blocking.
+fn start_mock() {
+    static START: Once = Once::new();
+    START.call_once(|| {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        anki_connect::configure("127.0.0.1", listener.local_addr().unwrap().port()).unwrap();
+
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                axum::serve(listener, Router::new().route("/", post(handle))).await.unwrap();
+            });
+        });
+    });
+}
+
+async fn call(tool: &dyn Tool, arguments: Value) -> CallToolResult {
+    start_mock();
+    let arguments: HashMap<String, Value> = serde_json::from_value(arguments).unwrap();
+    tool.call(arguments).await.unwrap()
+}
+
+/// The fields named by the `InvalidArguments` error `tool` fails with.
+async fn invalid_fields(tool: &dyn Tool, arguments: Value) -> Vec<String> {
+    start_mock();
+    let arguments: HashMap<String, Value> = serde_json::from_value(arguments).unwrap();
+    let error = tool.call(arguments).await.expect_err("arguments were accepted");
+    let invalid = error.downcast_ref::<InvalidArguments>().unwrap_or_else(|| panic!("not invalid arguments: {}", error));
+    invalid.errors.iter().map(|e| e.field.clone()).collect()
+}
+
+fn text(result: &CallToolResult) -> &str {
+    match &result.content[0] {
+        ToolResultContent::Text { text } => text,
+        content => panic!("unexpected content {:?}", content),
+    }
+}
+
+fn json_text(result: &CallToolResult) -> Value {
+    assert_eq!(result.is_error, Some(false), "{}", text(result));
+    serde_json::from_str(text(result)).unwrap()
+}
+
+#[tokio::test]
+async fn delete_notes_preview_rejects_missing_notes() {
+    let tool = DeleteNotesTool::new();
+
+    let result = call(&tool, json!({ "note_ids": [1, 999, 2, 998] })).await;
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("Notes not found: 998, 999"), "{}", text(&result));
+}
+
+#[tokio::test]
+async fn delete_notes_confirms_with_the_previewed_notes() {
+    let tool = DeleteNotesTool::new();
+
+    let preview = json_text(&call(&tool, json!({ "note_ids": [2, 1, 2] })).await);
+    assert_eq!(preview["total_notes"], 2);
+    assert_eq!(preview["total_cards"], 2);
+
+    let deleted = json_text(&call(&tool, json!({
+        "note_ids": [1, 2],
+        "confirm": true,
+        "preview_token": preview["preview_token"],
+    })).await);
+    assert_eq!(deleted["deleted_note_ids"], json!([1, 2]));
+}
+
+#[tokio::test]
+async fn get_cards_reports_cards_it_cannot_read() {
+    let result = call(&GetCardsTool, json!({ "card_ids": [10, 30] })).await;
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("Unexpected card in result of AnkiConnect action 'cardsInfo'"), "{}", text(&result));
+}
+
+/// A media directory holding `inside.png`, next to a file `outside.txt`.
+fn media_dir() -> (std::path::PathBuf, std::path::PathBuf) {
+    let root = std::env::temp_dir().join(format!("ankiconnect-mcp-test-{}", std::process::id()));
+    let dir = root.join("media");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("inside.png"), b"png").unwrap();
+    std::fs::write(root.join("outside.txt"), b"secret").unwrap();
+    (std::fs::canonicalize(&dir).unwrap(), root.join("outside.txt"))
+}
+
+fn store_path(path: &std::path::Path) -> Value {
+    json!({ "filename": "stored.png", "path": path.to_str().unwrap() })
+}
+
+#[tokio::test]
+async fn store_media_file_reads_files_inside_the_media_directory() {
+    let (dir, _) = media_dir();
+    let tool = StoreMediaFileTool::new(MediaPathAccess::Within(dir.clone()));
+
+    let stored = json_text(&call(&tool, store_path(&dir.join("inside.png"))).await);
+    assert_eq!(stored["filename"], "stored.png");
+}
+
+#[tokio::test]
+async fn store_media_file_refuses_files_outside_the_media_directory() {
+    let (dir, outside) = media_dir();
+    let tool = StoreMediaFileTool::new(MediaPathAccess::Within(dir.clone()));
+
+    for path in [outside, dir.join("..").join("outside.txt")] {
+        let result = call(&tool, store_path(&path)).await;
+        assert_eq!(result.is_error, Some(true));
+        assert!(text(&result).contains("is outside the media directory"), "{}", text(&result));
+    }
+}
+
+#[tokio::test]
+async fn store_media_file_refuses_paths_when_disabled() {
+    let (dir, _) = media_dir();
+    let tool = StoreMediaFileTool::new(MediaPathAccess::Disabled);
+
+    let result = call(&tool, store_path(&dir.join("inside.png"))).await;
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("does not read local files"), "{}", text(&result));
+}
+
+#[tokio::test]
+async fn rules_between_arguments_fail_as_invalid_arguments() {
+    assert_eq!(invalid_fields(&DeleteDeckTool, json!({ "deck": "Old", "cards": "move" })).await, ["move_to"]);
+    assert_eq!(invalid_fields(&DeleteNotesTool::new(), json!({ "note_ids": [1], "confirm": true })).await, ["preview_token"]);
+    assert_eq!(invalid_fields(&DeleteNotesTool::new(), json!({ "note_ids": [1], "query": "deck:Old" })).await, [""]);
+    assert_eq!(invalid_fields(&GetCardsTool, json!({})).await, [""]);
+    assert_eq!(invalid_fields(&ReviewStatsTool, json!({ "deck": "Old", "query": "deck:Old" })).await, [""]);
+}
+
+#[tokio::test]
+async fn update_note_tags_reports_every_invalid_tag() {
+    let fields = invalid_fields(&UpdateNoteTagsTool, json!({ "note_id": 1, "mode": "add", "tags": ["ok", "two words", ""] })).await;
+    assert_eq!(fields, ["tags/1", "tags/2"]);
+}
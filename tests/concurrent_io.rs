@@ -0,0 +1,239 @@
+//! Runs the server over an in-memory newline-delimited transport to check that
+//! reading a request is not disturbed by responses written at the same time,
+//! and how cancelled requests are answered.
+
+use ankiconnect_mcp::protocol::{CallToolResult, ToolResultContent};
+use ankiconnect_mcp::server::capabilities::Tool;
+use ankiconnect_mcp::server::tools::EchoTool;
+use ankiconnect_mcp::{LineTransport, McpServer};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, ReadHalf, WriteHalf};
+
+/// Answers after `ms` milliseconds, so its response is written while the
+/// client is still sending later requests.
+struct SlowTool;
+
+#[async_trait]
+impl Tool for SlowTool {
+    fn definition(&self, name: String) -> ankiconnect_mcp::protocol::Tool {
+        ankiconnect_mcp::protocol::Tool {
+            name,
+            description: None,
+            input_schema: Some(json!({ "type": "object" })),
+            output_schema: None,
+            annotations: None,
+        }
+    }
+
+    async fn call(&self, arguments: HashMap<String, Value>) -> Result<CallToolResult> {
+        let ms = arguments.get("ms").and_then(Value::as_u64).unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        Ok(CallToolResult {
+            content: vec![ToolResultContent::Text {
+                // A long response takes several writes to get through the pipe
+                text: "x".repeat(64 * 1024),
+            }],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
+
+/// Blocks its thread like a tool waiting on AnkiConnect, so cancellation
+/// cannot interrupt it.
+struct BlockingTool;
+
+#[async_trait]
+impl Tool for BlockingTool {
+    fn definition(&self, name: String) -> ankiconnect_mcp::protocol::Tool {
+        SlowTool.definition(name)
+    }
+
+    async fn call(&self, _arguments: HashMap<String, Value>) -> Result<CallToolResult> {
+        std::thread::sleep(Duration::from_millis(200));
+        Ok(CallToolResult {
+            content: vec![ToolResultContent::Text { text: "done".to_string() }],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
+
+struct Client {
+    reader: BufReader<ReadHalf<DuplexStream>>,
+    writer: WriteHalf<DuplexStream>,
+}
+
+impl Client {
+    async fn send_raw(&mut self, bytes: &[u8]) {
+        self.writer.write_all(bytes).await.unwrap();
+        self.writer.flush().await.unwrap();
+    }
+
+    async fn send(&mut self, message: Value) {
+        self.send_raw(format!("{}\n", message).as_bytes()).await;
+    }
+
+    async fn receive(&mut self) -> Value {
+        receive(&mut self.reader).await
+    }
+}
+
+async fn receive(reader: &mut BufReader<ReadHalf<DuplexStream>>) -> Value {
+    let mut line = String::new();
+    tokio::time::timeout(Duration::from_secs(10), reader.read_line(&mut line))
+        .await
+        .expect("timed out waiting for a message")
+        .unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn call(id: u64, tool: &str, arguments: Value) -> Value {
+    request(id, "tools/call", json!({ "name": tool, "arguments": arguments }))
+}
+
+async fn start() -> Client {
+    let mut server = McpServer::new();
+    server.add_tool("echo".to_string(), Box::new(EchoTool));
+    server.add_tool("slow".to_string(), Box::new(SlowTool));
+    server.add_tool("blocking".to_string(), Box::new(BlockingTool));
+
+    // A small pipe, so the server's writes block until the client reads
+    let (client, server_side) = tokio::io::duplex(1024);
+    let (server_reader, server_writer) = tokio::io::split(server_side);
+    tokio::spawn(async move {
+        let _ = server.run(LineTransport::new(server_reader, server_writer)).await;
+    });
+
+    let (reader, writer) = tokio::io::split(client);
+    let mut client = Client { reader: BufReader::new(reader), writer };
+
+    client.send(request(1, "initialize", json!({
+        "protocolVersion": "2024-11-05",
+        "capabilities": {},
+        "clientInfo": { "name": "test", "version": "1.0" },
+    }))).await;
+    assert_eq!(client.receive().await["id"], 1);
+    client.send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+
+    client
+}
+
+fn echoed_text(response: &Value) -> &str {
+    response["result"]["content"][0]["text"].as_str().unwrap_or_default()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_split_around_a_response_is_read_intact() {
+    let mut client = start().await;
+
+    client.send(call(2, "slow", json!({ "ms": 100 }))).await;
+
+    // Send half of the next request, then let the slow response be written while
+    // the server is in the middle of reading that line
+    let echo = format!("{}\n", call(3, "echo", json!({ "text": "intact" })));
+    let (head, tail) = echo.as_bytes().split_at(echo.len() / 2);
+    client.send_raw(head).await;
+
+    let slow = client.receive().await;
+    assert_eq!(slow["id"], 2);
+    assert!(slow["result"].is_object(), "{}", slow);
+
+    client.send_raw(tail).await;
+    let response = client.receive().await;
+    assert_eq!(response["id"], 3);
+    assert_eq!(response["result"]["isError"], false);
+    assert_eq!(echoed_text(&response), "Echo: intact");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requests_trickling_in_while_responses_are_written_are_all_answered() {
+    let mut client = start().await;
+
+    for id in 2..12 {
+        client.send(call(id, "slow", json!({ "ms": (id % 3) * 5 }))).await;
+    }
+
+    // Byte by byte, so reads are interrupted by the slow responses many times.
+    // Written from another task, as the server stops reading while the client
+    // is not taking its responses
+    let mut expected = HashMap::new();
+    let mut lines = Vec::new();
+    for id in 100..110 {
+        let text = format!("request {}", id);
+        lines.push(format!("{}\n", call(id, "echo", json!({ "text": text }))));
+        expected.insert(id, format!("Echo: {}", text));
+    }
+    let Client { mut reader, mut writer } = client;
+    let sender = tokio::spawn(async move {
+        for byte in lines.concat().as_bytes() {
+            writer.write_all(std::slice::from_ref(byte)).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+    });
+
+    let mut slow_responses = 0;
+    while slow_responses < 10 || !expected.is_empty() {
+        let response = receive(&mut reader).await;
+        let id = response["id"].as_u64().unwrap();
+        assert!(response.get("error").is_none(), "{}", response);
+        match expected.remove(&id) {
+            Some(text) => assert_eq!(echoed_text(&response), text),
+            None => {
+                assert!((2..12).contains(&id), "{}", response);
+                slow_responses += 1;
+            }
+        }
+    }
+
+    sender.await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelled_request_reports_its_result_as_a_log_message() {
+    let mut client = start().await;
+
+    client.send(call(2, "blocking", json!({}))).await;
+    client.send(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": 2, "reason": "test" },
+    })).await;
+
+    let message = client.receive().await;
+    assert_eq!(message["method"], "notifications/message", "{}", message);
+    assert_eq!(message["params"]["level"], "notice");
+    assert_eq!(message["params"]["data"]["requestId"], 2);
+    assert_eq!(message["params"]["data"]["result"]["content"][0]["text"], "done");
+
+    // No response follows for the cancelled request
+    client.send(request(3, "ping", json!({}))).await;
+    assert_eq!(client.receive().await["id"], 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelled_request_result_is_not_reported_above_the_log_level() {
+    let mut client = start().await;
+
+    client.send(request(2, "logging/setLevel", json!({ "level": "warning" }))).await;
+    assert_eq!(client.receive().await["id"], 2);
+
+    client.send(call(3, "blocking", json!({}))).await;
+    client.send(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": { "requestId": 3 },
+    })).await;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    client.send(request(4, "ping", json!({}))).await;
+    assert_eq!(client.receive().await["id"], 4);
+}
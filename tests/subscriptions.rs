@@ -0,0 +1,119 @@
+//! Runs two sessions of one server over in-memory transports to check that
+//! they share the polling of the resources they subscribed to.
+
+use ankiconnect_mcp::protocol::ResourceContent;
+use ankiconnect_mcp::server::capabilities::Resource;
+use ankiconnect_mcp::{LineTransport, McpServer};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, ReadHalf, WriteHalf};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A resource whose contents are `version`, counting how often it is read.
+#[derive(Clone, Default)]
+struct CountingResource {
+    version: Arc<AtomicUsize>,
+    reads: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Resource for CountingResource {
+    fn definition(&self, uri: String) -> ankiconnect_mcp::protocol::Resource {
+        ankiconnect_mcp::protocol::Resource {
+            uri,
+            name: "counter".to_string(),
+            description: None,
+            mime_type: Some("text/plain".to_string()),
+        }
+    }
+
+    async fn read(&self) -> Result<Vec<ResourceContent>> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![ResourceContent::Text {
+            uri: "test://counter".to_string(),
+            text: self.version.load(Ordering::SeqCst).to_string(),
+            mime_type: Some("text/plain".to_string()),
+        }])
+    }
+}
+
+struct Client {
+    reader: BufReader<ReadHalf<DuplexStream>>,
+    writer: WriteHalf<DuplexStream>,
+}
+
+impl Client {
+    async fn send(&mut self, message: Value) {
+        self.writer.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+        self.writer.flush().await.unwrap();
+    }
+
+    async fn receive(&mut self) -> Value {
+        let mut line = String::new();
+        tokio::time::timeout(Duration::from_secs(10), self.reader.read_line(&mut line))
+            .await
+            .expect("timed out waiting for a message")
+            .unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+/// Starts a session of `server` and subscribes it to the counter.
+async fn subscribe(server: &McpServer) -> Client {
+    let session = server.new_session();
+    let (client, server_side) = tokio::io::duplex(64 * 1024);
+    let (server_reader, server_writer) = tokio::io::split(server_side);
+    tokio::spawn(async move {
+        let _ = session.run(LineTransport::new(server_reader, server_writer)).await;
+    });
+
+    let (reader, writer) = tokio::io::split(client);
+    let mut client = Client { reader: BufReader::new(reader), writer };
+
+    client.send(request(1, "initialize", json!({
+        "protocolVersion": "2024-11-05",
+        "capabilities": {},
+        "clientInfo": { "name": "test", "version": "1.0" },
+    }))).await;
+    assert_eq!(client.receive().await["id"], 1);
+    client.send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+
+    client.send(request(2, "resources/subscribe", json!({ "uri": "test://counter" }))).await;
+    let response = client.receive().await;
+    assert!(response["result"].is_object(), "{}", response);
+
+    client
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sessions_subscribed_to_a_resource_share_its_polling() {
+    let resource = CountingResource::default();
+    let mut server = McpServer::new();
+    server.add_resource("test://counter".to_string(), Box::new(resource.clone()));
+    server.set_subscription_poll_interval(POLL_INTERVAL);
+
+    let mut first = subscribe(&server).await;
+    let mut second = subscribe(&server).await;
+
+    resource.version.fetch_add(1, Ordering::SeqCst);
+    for client in [&mut first, &mut second] {
+        let notification = client.receive().await;
+        assert_eq!(notification["method"], "notifications/resources/updated", "{}", notification);
+        assert_eq!(notification["params"]["uri"], "test://counter");
+    }
+
+    // One read per interval; a poller per session would read twice as often
+    let reads = resource.reads.load(Ordering::SeqCst);
+    tokio::time::sleep(POLL_INTERVAL * 10).await;
+    let polled = resource.reads.load(Ordering::SeqCst) - reads;
+    assert!((6..=14).contains(&polled), "read {} times in 10 intervals", polled);
+}